@@ -0,0 +1,140 @@
+use syn::spanned::Spanned;
+use syn::{Error, Expr, ExprClosure, Ident, Pat, Result, Type};
+
+pub struct Field<'l> {
+    pub name: &'l Ident,
+    pub kind: &'l Type,
+    pub constant: Option<Expr>,
+    pub read: Option<ExprClosure>,
+    pub write: Option<ExprClosure>,
+    pub given: Option<Expr>,
+    pub condition: Option<Expr>,
+    pub offset: Option<Expr>,
+}
+
+impl<'l> Field<'l> {
+    pub fn parse(input: &'l syn::DeriveInput) -> Result<Vec<Self>> {
+        let fields = match &input.data {
+            syn::Data::Struct(syn::DataStruct {
+                fields: syn::Fields::Named(fields),
+                ..
+            }) => &fields.named,
+            _ => {
+                return Err(Error::new(
+                    input.span(),
+                    "expected a structure with named fields",
+                ))
+            }
+        };
+        fields.iter().map(Field::new).collect()
+    }
+
+    /// Whether the field needs the position of the table.
+    pub fn positioned(&self) -> bool {
+        self.offset.is_some() || matches!(&self.read, Some(closure) if closure.inputs.len() == 3)
+    }
+
+    /// The type that is read or written, which is the inner type for conditional fields.
+    pub fn inner(&self) -> Result<&'l Type> {
+        if self.condition.is_none() {
+            return Ok(self.kind);
+        }
+        if let Type::Path(path) = self.kind {
+            if let Some(segment) = path.path.segments.last() {
+                if segment.ident == "Option" {
+                    if let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments {
+                        if let Some(syn::GenericArgument::Type(kind)) = arguments.args.first() {
+                            return Ok(kind);
+                        }
+                    }
+                }
+            }
+        }
+        Err(Error::new(
+            self.kind.span(),
+            "expected a field of type Option for a condition",
+        ))
+    }
+
+    fn new(field: &'l syn::Field) -> Result<Self> {
+        let mut this = Field {
+            name: field.ident.as_ref().unwrap(),
+            kind: &field.ty,
+            constant: None,
+            read: None,
+            write: None,
+            given: None,
+            condition: None,
+            offset: None,
+        };
+        for attribute in field.attrs.iter() {
+            if !attribute.path().is_ident("typeface") {
+                continue;
+            }
+            attribute.parse_nested_meta(|meta| {
+                let key = match meta.path.get_ident() {
+                    Some(key) => key.to_string(),
+                    _ => return Err(meta.error("expected a key")),
+                };
+                let value = meta.value()?;
+                match key.as_str() {
+                    "constant" => this.constant = Some(value.parse()?),
+                    "read" => this.read = Some(closure(value.parse()?, &[2, 3])?),
+                    "write" => this.write = Some(closure(value.parse()?, &[2])?),
+                    "given" => this.given = Some(value.parse()?),
+                    "condition" => this.condition = Some(value.parse()?),
+                    "offset" => this.offset = Some(value.parse()?),
+                    _ => return Err(meta.error(format!("found an unknown key {key}"))),
+                }
+                Ok(())
+            })?;
+        }
+        let sources = [
+            this.constant.is_some(),
+            this.read.is_some(),
+            this.given.is_some(),
+            this.offset.is_some(),
+        ];
+        if sources.iter().filter(|&&source| source).count() > 1 {
+            return Err(Error::new(
+                field.span(),
+                "expected at most one of constant, read, given, and offset",
+            ));
+        }
+        Ok(this)
+    }
+}
+
+/// Assign types to the arguments of a closure.
+pub fn arguments(
+    closure: &ExprClosure,
+    kinds: &[proc_macro2::TokenStream],
+) -> Vec<proc_macro2::TokenStream> {
+    closure
+        .inputs
+        .iter()
+        .zip(kinds)
+        .map(|(input, kind)| {
+            let input = match input {
+                Pat::Type(input) => &*input.pat,
+                input => input,
+            };
+            quote::quote!(#input: #kind)
+        })
+        .collect()
+}
+
+fn closure(closure: ExprClosure, counts: &[usize]) -> Result<ExprClosure> {
+    if !counts.contains(&closure.inputs.len()) {
+        let counts = counts
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" or ");
+        return Err(Error::new(
+            closure.span(),
+            format!("expected a closure with {counts} arguments"),
+        ));
+    }
+    Ok(closure)
+}
@@ -0,0 +1,43 @@
+//! Derive macros for building and parsing fonts.
+//!
+//! The macros are meant to be used via the `derive` feature of the `typeface` crate, which
+//! reexports them as `typeface::value::Read` and `typeface::value::Write`. They produce the same
+//! code as `table!` does but work with generics, lifetimes, and the usual tooling.
+//!
+//! Each field can be annotated with `#[typeface(...)]` using the following keys:
+//!
+//! * `constant = <expression>` checks the field against a value when reading;
+//! * `read = |this, tape| ...` or `read = |this, tape, position| ...` reads the field using a
+//!   closure given the table read so far, the tape, and the position of the table;
+//! * `write = |this, tape| ...` writes the field using a closure given the table and the tape;
+//! * `given = <expression>` reads the field given a parameter, which can refer to `this`;
+//! * `condition = <expression>` reads an `Option` field only when the condition, which can
+//!   refer to `this`, holds and writes it only when it is present; and
+//! * `offset = <expression>` reads the field at an offset from the position of the table,
+//!   which can refer to `this`, and then returns to where the tape was.
+
+mod field;
+mod read;
+mod write;
+
+use proc_macro::TokenStream;
+
+/// Derive `typeface::value::Read`.
+#[proc_macro_derive(Read, attributes(typeface))]
+pub fn read(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    match read::implement(&input) {
+        Ok(output) => output.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// Derive `typeface::value::Write`.
+#[proc_macro_derive(Write, attributes(typeface))]
+pub fn write(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    match write::implement(&input) {
+        Ok(output) => output.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
@@ -0,0 +1,106 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, DeriveInput, Ident, Result};
+
+use crate::field::{arguments, Field};
+
+pub fn implement(input: &DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    let fields = Field::parse(input)?;
+    let mut generics = input.generics.clone();
+    for parameter in generics.type_params_mut() {
+        parameter.bounds.push(parse_quote!(::typeface::value::Read));
+    }
+    generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote!(Self: Default));
+    let (implementation, kind, clause) = generics.split_for_impl();
+    let position = if fields.iter().any(Field::positioned) {
+        quote!(let position = tape.position()?;)
+    } else {
+        quote!()
+    };
+    let statements = fields
+        .iter()
+        .map(|field| statement(name, field))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(quote! {
+        impl #implementation ::typeface::value::Read for #name #kind #clause {
+            fn read<__T: ::typeface::tape::Read>(tape: &mut __T) -> ::typeface::Result<Self> {
+                #position
+                let mut table: Self = Default::default();
+                #(#statements)*
+                Ok(table)
+            }
+        }
+    })
+}
+
+fn statement(name: &Ident, field: &Field) -> Result<TokenStream> {
+    let field_name = field.name;
+    let kind = field.inner()?;
+    let mut value = if let Some(closure) = &field.read {
+        let arguments = arguments(closure, &[quote!(&Self), quote!(&mut __T), quote!(u64)]);
+        let body = &closure.body;
+        let call = if closure.inputs.len() == 3 {
+            quote!(read(&table, tape, position))
+        } else {
+            quote!(read(&table, tape))
+        };
+        quote!({
+            let read = |#(#arguments),*| -> ::typeface::Result<#kind> { #body };
+            #call?
+        })
+    } else if let Some(given) = &field.given {
+        quote!({
+            #[allow(unused_variables)]
+            let this = &table;
+            tape.take_given::<#kind>(#given)?
+        })
+    } else if let Some(offset) = &field.offset {
+        quote!({
+            #[allow(unused_variables)]
+            let this = &table;
+            let offset = (#offset) as u64;
+            tape.stay(|tape| {
+                tape.jump(position + offset)?;
+                tape.take::<#kind>()
+            })?
+        })
+    } else {
+        quote!(tape.take::<#kind>()?)
+    };
+    if let Some(constant) = &field.constant {
+        value = quote!({
+            let value: #kind = #value;
+            if value != #constant {
+                ::typeface::raise!(
+                    concat!(
+                        "found a malformed field ",
+                        stringify!(#name), "::", stringify!(#field_name),
+                        " with value {:?} unequal to {:?}",
+                    ),
+                    value,
+                    #constant,
+                );
+            }
+            value
+        });
+    }
+    if let Some(condition) = &field.condition {
+        value = quote!({
+            #[allow(unused_variables)]
+            let this = &table;
+            if #condition {
+                Some(#value)
+            } else {
+                None
+            }
+        });
+    }
+    Ok(quote! {
+        let value = #value;
+        table.#field_name = value;
+    })
+}
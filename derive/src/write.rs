@@ -0,0 +1,64 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, DeriveInput, Error, Ident, Result};
+
+use crate::field::{arguments, Field};
+
+pub fn implement(input: &DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    let fields = Field::parse(input)?;
+    let mut generics = input.generics.clone();
+    for parameter in generics.type_params_mut() {
+        parameter
+            .bounds
+            .push(parse_quote!(::typeface::value::Write));
+    }
+    let (implementation, kind, clause) = generics.split_for_impl();
+    let statements = fields
+        .iter()
+        .map(|field| statement(name, field))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(quote! {
+        impl #implementation ::typeface::value::Write for #name #kind #clause {
+            fn write<__T: ::typeface::tape::Write>(&self, tape: &mut __T) -> ::typeface::Result<()> {
+                #(#statements)*
+                Ok(())
+            }
+        }
+    })
+}
+
+fn statement(name: &Ident, field: &Field) -> Result<TokenStream> {
+    let field_name = field.name;
+    if let Some(closure) = &field.write {
+        let arguments = arguments(closure, &[quote!(&Self), quote!(&mut __T)]);
+        let body = &closure.body;
+        return Ok(quote!({
+            let write = |#(#arguments),*| -> ::typeface::Result<()> { #body };
+            write(self, tape)?;
+        }));
+    }
+    if field.offset.is_some() {
+        return Err(Error::new(
+            field.name.span(),
+            "expected a write closure for a field with an offset",
+        ));
+    }
+    if let Some(condition) = &field.condition {
+        field.inner()?;
+        return Ok(quote!({
+            #[allow(unused_variables)]
+            let this = self;
+            match (&self.#field_name, #condition) {
+                (Some(value), true) => tape.give(value)?,
+                (None, false) => {}
+                _ => ::typeface::raise!(concat!(
+                    "found a field ",
+                    stringify!(#name), "::", stringify!(#field_name),
+                    " whose presence disagrees with its condition",
+                )),
+            }
+        }));
+    }
+    Ok(quote!(tape.give(&self.#field_name)?;))
+}
@@ -0,0 +1,70 @@
+use std::io::Cursor;
+
+use typeface::tape::{Read as _, Write as _};
+use typeface::value::{Read, Write};
+
+#[derive(Clone, Debug, Default, PartialEq, Read, Write)]
+pub struct Table<'l, T> {
+    #[typeface(constant = 1)]
+    major_version: u16,
+    minor_version: u16,
+    count: u16,
+    #[typeface(given = this.count as usize)]
+    records: Vec<T>,
+    #[typeface(condition = this.minor_version > 0)]
+    extra: Option<u16>,
+    #[typeface(
+        read = |this, tape, position| {
+            let _ = (this, position);
+            tape.take()
+        },
+        write = |this, tape| tape.give(&this.data),
+    )]
+    data: [u8; 2],
+    #[typeface(read = |_, _| Ok(&[][..]), write = |_, _| Ok(()))]
+    empty: &'l [u8],
+}
+
+#[derive(Debug, Default, Read)]
+pub struct Offset {
+    offset: u16,
+    #[typeface(offset = this.offset)]
+    value: u16,
+    next: u16,
+}
+
+#[test]
+fn read_write() {
+    let table = Table::<u16> {
+        major_version: 1,
+        minor_version: 1,
+        count: 2,
+        records: vec![3, 4],
+        extra: Some(5),
+        data: [6, 7],
+        empty: &[],
+    };
+    let mut tape = Cursor::new(vec![]);
+    tape.give(&table).unwrap();
+    assert_eq!(tape.get_ref(), &[0, 1, 0, 1, 0, 2, 0, 3, 0, 4, 0, 5, 6, 7]);
+    tape.set_position(0);
+    assert_eq!(tape.take::<Table<u16>>().unwrap(), table);
+
+    let mut tape = Cursor::new(vec![0, 2, 0, 1, 0, 3, 0, 4]);
+    assert!(tape.take::<Table<u16>>().is_err());
+
+    let mut tape = Cursor::new(vec![]);
+    let table = Table::<u16> {
+        major_version: 1,
+        extra: Some(5),
+        ..Default::default()
+    };
+    assert!(tape.give(&table).is_err());
+}
+
+#[test]
+fn offset() {
+    let mut tape = Cursor::new(vec![0, 6, 0, 1, 0, 0, 0, 2]);
+    let table = tape.take::<Offset>().unwrap();
+    assert_eq!((table.offset, table.value, table.next), (6, 2, 1));
+}
@@ -0,0 +1,355 @@
+use std::marker::PhantomData;
+
+use crate::encoding::{Encoding, Mode};
+use crate::Result;
+
+/// A collection preceded by its count.
+///
+/// The count is of type `C`, and it is computed from the length when writing.
+pub struct Counted<C, T> {
+    values: Vec<T>,
+    count: PhantomData<C>,
+}
+
+/// A string preceded by its length in bytes.
+///
+/// The length is of type `C`, and the content is encoded in Mac OS Roman.
+pub struct PascalString<C = u8> {
+    value: String,
+    count: PhantomData<C>,
+}
+
+macro_rules! implement {
+    ($name:ident<$($parameter:ident),*>::$field:ident => $target:ty) => {
+        impl<$($parameter),*> Clone for $name<$($parameter),*>
+        where
+            $target: Clone,
+        {
+            #[inline]
+            fn clone(&self) -> Self {
+                self.$field.clone().into()
+            }
+        }
+
+        impl<$($parameter),*> std::fmt::Debug for $name<$($parameter),*>
+        where
+            $target: std::fmt::Debug,
+        {
+            #[inline]
+            fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                self.$field.fmt(formatter)
+            }
+        }
+
+        impl<$($parameter),*> Default for $name<$($parameter),*> {
+            #[inline]
+            fn default() -> Self {
+                <$target>::default().into()
+            }
+        }
+
+        impl<$($parameter),*> Eq for $name<$($parameter),*> where $target: Eq {}
+
+        impl<$($parameter),*> std::hash::Hash for $name<$($parameter),*>
+        where
+            $target: std::hash::Hash,
+        {
+            #[inline]
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.$field.hash(state)
+            }
+        }
+
+        impl<$($parameter),*> PartialEq for $name<$($parameter),*>
+        where
+            $target: PartialEq,
+        {
+            #[inline]
+            fn eq(&self, other: &Self) -> bool {
+                self.$field == other.$field
+            }
+        }
+
+        impl<$($parameter),*> std::ops::Deref for $name<$($parameter),*> {
+            type Target = $target;
+
+            #[inline]
+            fn deref(&self) -> &Self::Target {
+                &self.$field
+            }
+        }
+
+        impl<$($parameter),*> std::ops::DerefMut for $name<$($parameter),*> {
+            #[inline]
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.$field
+            }
+        }
+    };
+}
+
+implement!(Counted<C, T>::values => Vec<T>);
+implement!(PascalString<C>::value => String);
+
+impl<C, T> From<Vec<T>> for Counted<C, T> {
+    #[inline]
+    fn from(values: Vec<T>) -> Self {
+        Self {
+            values,
+            count: PhantomData,
+        }
+    }
+}
+
+impl<C, T> From<Counted<C, T>> for Vec<T> {
+    #[inline]
+    fn from(value: Counted<C, T>) -> Self {
+        value.values
+    }
+}
+
+impl<C> From<String> for PascalString<C> {
+    #[inline]
+    fn from(value: String) -> Self {
+        Self {
+            value,
+            count: PhantomData,
+        }
+    }
+}
+
+impl<C> From<&str> for PascalString<C> {
+    #[inline]
+    fn from(value: &str) -> Self {
+        value.to_string().into()
+    }
+}
+
+impl<C> From<PascalString<C>> for String {
+    #[inline]
+    fn from(value: PascalString<C>) -> Self {
+        value.value
+    }
+}
+
+impl<C, T> crate::value::Read for Counted<C, T>
+where
+    C: crate::value::Read + TryInto<usize>,
+    T: crate::value::Read,
+{
+    fn read<U: crate::tape::Read>(tape: &mut U) -> Result<Self> {
+        let count = read_count::<C, U>(tape)?;
+        Ok(tape.take_given::<Vec<T>>(count)?.into())
+    }
+}
+
+impl<C, T> crate::value::Write for Counted<C, T>
+where
+    C: crate::value::Write + TryFrom<usize>,
+    T: crate::value::Write,
+{
+    fn write<U: crate::tape::Write>(&self, tape: &mut U) -> Result<()> {
+        write_count::<C, U>(tape, self.values.len())?;
+        tape.give(&self.values[..])
+    }
+}
+
+impl<C> crate::value::Read for PascalString<C>
+where
+    C: crate::value::Read + TryInto<usize>,
+{
+    fn read<U: crate::tape::Read>(tape: &mut U) -> Result<Self> {
+        let count = read_count::<C, U>(tape)?;
+        Ok(tape
+            .take_given::<String>((count, Encoding::MacRoman, Mode::Strict))?
+            .into())
+    }
+}
+
+impl<C> crate::value::Write for PascalString<C>
+where
+    C: crate::value::Write + TryFrom<usize>,
+{
+    fn write<U: crate::tape::Write>(&self, tape: &mut U) -> Result<()> {
+        let value = Encoding::MacRoman.encode(&self.value, Mode::Strict)?;
+        write_count::<C, U>(tape, value.len())?;
+        tape.give_bytes(&value)
+    }
+}
+
+impl<C, T> crate::value::Size for Counted<C, T>
+where
+    C: crate::value::Size + Default,
+    T: crate::value::Size,
+{
+    #[inline]
+    fn size(&self) -> usize {
+        C::default().size() + self.values.size()
+    }
+}
+
+impl<C> crate::value::Size for PascalString<C>
+where
+    C: crate::value::Size + Default,
+{
+    #[inline]
+    fn size(&self) -> usize {
+        let value = Encoding::MacRoman.encode(&self.value, Mode::Lossy);
+        C::default().size() + value.map_or(0, |value| value.len())
+    }
+}
+
+impl<C, T> crate::visit::Visit for Counted<C, T>
+where
+    T: crate::visit::Visit,
+{
+    #[inline]
+    fn visit(&self, visitor: &mut dyn crate::visit::Visitor) {
+        self.values.visit(visitor)
+    }
+}
+
+impl<C> crate::visit::Visit for PascalString<C> {
+    #[inline]
+    fn visit(&self, visitor: &mut dyn crate::visit::Visitor) {
+        self.value.visit(visitor)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<C, T> crate::serde::Serialize for Counted<C, T>
+where
+    T: crate::serde::Serialize,
+{
+    #[inline]
+    fn serialize<U>(&self, serializer: U) -> std::result::Result<U::Ok, U::Error>
+    where
+        U: crate::serde::Serializer,
+    {
+        self.values.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'l, C, T> crate::serde::Deserialize<'l> for Counted<C, T>
+where
+    T: crate::serde::Deserialize<'l>,
+{
+    #[inline]
+    fn deserialize<U>(deserializer: U) -> std::result::Result<Self, U::Error>
+    where
+        U: crate::serde::Deserializer<'l>,
+    {
+        Vec::<T>::deserialize(deserializer).map(Into::into)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<C> crate::serde::Serialize for PascalString<C> {
+    #[inline]
+    fn serialize<U>(&self, serializer: U) -> std::result::Result<U::Ok, U::Error>
+    where
+        U: crate::serde::Serializer,
+    {
+        self.value.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'l, C> crate::serde::Deserialize<'l> for PascalString<C> {
+    #[inline]
+    fn deserialize<U>(deserializer: U) -> std::result::Result<Self, U::Error>
+    where
+        U: crate::serde::Deserializer<'l>,
+    {
+        String::deserialize(deserializer).map(Into::into)
+    }
+}
+
+#[cfg(feature = "ttx")]
+impl<C, T> crate::ttx::Load for Counted<C, T>
+where
+    T: crate::ttx::Load,
+{
+    #[inline]
+    fn load(element: &crate::ttx::Element) -> Result<Self> {
+        Vec::<T>::load(element).map(Into::into)
+    }
+
+    #[inline]
+    fn load_all(elements: &[&crate::ttx::Element]) -> Result<Self> {
+        Vec::<T>::load_all(elements).map(Into::into)
+    }
+}
+
+#[cfg(feature = "ttx")]
+impl<C> crate::ttx::Load for PascalString<C> {
+    #[inline]
+    fn load(element: &crate::ttx::Element) -> Result<Self> {
+        String::load(element).map(Into::into)
+    }
+}
+
+fn read_count<C, T>(tape: &mut T) -> Result<usize>
+where
+    C: crate::value::Read + TryInto<usize>,
+    T: crate::tape::Read,
+{
+    match tape.take::<C>()?.try_into() {
+        Ok(count) => Ok(count),
+        _ => error!("found a count exceeding the addressable size"),
+    }
+}
+
+fn write_count<C, T>(tape: &mut T, count: usize) -> Result<()>
+where
+    C: crate::value::Write + TryFrom<usize>,
+    T: crate::tape::Write,
+{
+    match C::try_from(count) {
+        Ok(count) => tape.give(&count),
+        _ => error!(
+            "found a count of {} exceeding the range of type {}",
+            count,
+            std::any::type_name::<C>(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::{Counted, PascalString};
+    use crate::tape::{Read, Write};
+    use crate::value::Size;
+
+    #[test]
+    fn counted() {
+        let mut tape = Cursor::new(vec![]);
+        let value: Counted<u16, u16> = vec![1, 2, 3].into();
+        tape.give(&value).unwrap();
+        assert_eq!(tape.get_ref(), &[0, 3, 0, 1, 0, 2, 0, 3]);
+        assert_eq!(value.size(), 8);
+        tape.set_position(0);
+        assert_eq!(tape.take::<Counted<u16, u16>>().unwrap(), value);
+
+        let mut tape = Cursor::new(vec![]);
+        let value: Counted<u8, u8> = vec![0; 256].into();
+        assert!(tape.give(&value).is_err());
+
+        let mut tape = Cursor::new(vec![0xFF, 0xFF, 0xFF, 0xFF, 0, 1]);
+        assert!(tape.take::<Counted<u32, i64>>().is_err());
+    }
+
+    #[test]
+    fn pascal_string() {
+        let mut tape = Cursor::new(vec![]);
+        let value: PascalString = "café".into();
+        tape.give(&value).unwrap();
+        assert_eq!(tape.get_ref(), &[4, b'c', b'a', b'f', 0x8E]);
+        assert_eq!(value.size(), 5);
+        tape.set_position(0);
+        assert_eq!(tape.take::<PascalString>().unwrap(), value);
+    }
+}
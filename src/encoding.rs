@@ -0,0 +1,249 @@
+//! Encodings of strings.
+
+mod mac;
+
+use crate::Result;
+
+/// An encoding.
+///
+/// The Mac OS encodings cover the single-byte scripts whose mappings ship with the crate. The
+/// multi-byte Chinese, Japanese, and Korean Mac OS scripts are not supported yet.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Encoding {
+    /// UTF-16 in the big-endian byte order.
+    Utf16BE,
+    /// ISO 8859-1.
+    Latin1,
+    /// Mac OS Arabic.
+    MacArabic,
+    /// Mac OS Central European.
+    MacCentralEuropean,
+    /// Mac OS Croatian.
+    MacCroatian,
+    /// Mac OS Cyrillic.
+    MacCyrillic,
+    /// Mac OS Farsi.
+    MacFarsi,
+    /// Mac OS Greek.
+    MacGreek,
+    /// Mac OS Icelandic.
+    MacIcelandic,
+    /// Mac OS Roman.
+    MacRoman,
+    /// Mac OS Romanian.
+    MacRomanian,
+    /// Mac OS Turkish.
+    MacTurkish,
+}
+
+/// A coding mode.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Mode {
+    /// Fail on malformed data or unrepresentable characters.
+    #[default]
+    Strict,
+    /// Replace malformed data with U+FFFD and unrepresentable characters with `?`.
+    Lossy,
+}
+
+impl Encoding {
+    /// Decode a string.
+    pub fn decode(&self, bytes: &[u8], mode: Mode) -> Result<String> {
+        match self.table() {
+            Some(table) => Ok(bytes
+                .iter()
+                .map(|&byte| match byte {
+                    0x00..=0x7F => byte as char,
+                    _ => table[byte as usize - 0x80],
+                })
+                .collect()),
+            None if *self == Encoding::Utf16BE => {
+                let mut chunks = bytes.chunks_exact(2);
+                let units = chunks
+                    .by_ref()
+                    .map(|chunk| u16::from_be_bytes([chunk[0], chunk[1]]));
+                let mut value = String::with_capacity(bytes.len() / 2);
+                for character in char::decode_utf16(units) {
+                    match character {
+                        Ok(character) => value.push(character),
+                        Err(_) if mode == Mode::Lossy => value.push(char::REPLACEMENT_CHARACTER),
+                        Err(error) => raise!(
+                            "found a malformed string with an unpaired surrogate {:#06x}",
+                            error.unpaired_surrogate(),
+                        ),
+                    }
+                }
+                if !chunks.remainder().is_empty() {
+                    if mode == Mode::Strict {
+                        raise!("found a malformed string with an odd number of bytes");
+                    }
+                    value.push(char::REPLACEMENT_CHARACTER);
+                }
+                Ok(value)
+            }
+            None => Ok(bytes.iter().map(|&byte| byte as char).collect()),
+        }
+    }
+
+    /// Encode a string.
+    pub fn encode(&self, value: &str, mode: Mode) -> Result<Vec<u8>> {
+        match self.table() {
+            Some(table) => value
+                .chars()
+                .map(|character| match character as u32 {
+                    value @ 0x00..=0x7F => Ok(value as u8),
+                    _ => match table.iter().position(|&other| other == character) {
+                        Some(index) => Ok((index + 0x80) as u8),
+                        _ => self.unrepresentable(character, mode),
+                    },
+                })
+                .collect(),
+            None if *self == Encoding::Utf16BE => Ok(value
+                .encode_utf16()
+                .flat_map(|unit| unit.to_be_bytes())
+                .collect()),
+            None => value
+                .chars()
+                .map(|character| match character as u32 {
+                    value @ 0x00..=0xFF => Ok(value as u8),
+                    _ => self.unrepresentable(character, mode),
+                })
+                .collect(),
+        }
+    }
+
+    fn table(&self) -> Option<&'static [char; 128]> {
+        match self {
+            Encoding::Utf16BE | Encoding::Latin1 => None,
+            Encoding::MacArabic => Some(&mac::MAC_ARABIC),
+            Encoding::MacCentralEuropean => Some(&mac::MAC_CENTRAL_EUROPEAN),
+            Encoding::MacCroatian => Some(&mac::MAC_CROATIAN),
+            Encoding::MacCyrillic => Some(&mac::MAC_CYRILLIC),
+            Encoding::MacFarsi => Some(&mac::MAC_FARSI),
+            Encoding::MacGreek => Some(&mac::MAC_GREEK),
+            Encoding::MacIcelandic => Some(&mac::MAC_ICELANDIC),
+            Encoding::MacRoman => Some(&mac::MAC_ROMAN),
+            Encoding::MacRomanian => Some(&mac::MAC_ROMANIAN),
+            Encoding::MacTurkish => Some(&mac::MAC_TURKISH),
+        }
+    }
+
+    fn unrepresentable(&self, character: char, mode: Mode) -> Result<u8> {
+        if mode == Mode::Lossy {
+            return Ok(b'?');
+        }
+        error!(
+            "found a character {:?} unrepresentable in encoding {:?}",
+            character, self,
+        )
+    }
+}
+
+impl crate::walue::Read<'static> for String {
+    /// The number of bytes, the encoding, and the decoding mode.
+    type Parameter = (usize, Encoding, Mode);
+
+    fn read<T: crate::tape::Read>(
+        tape: &mut T,
+        (size, encoding, mode): Self::Parameter,
+    ) -> Result<Self> {
+        encoding.decode(&tape.take_bytes(size)?, mode)
+    }
+}
+
+impl crate::walue::Write<'static> for str {
+    /// The number of bytes, the encoding, and the encoding mode.
+    type Parameter = (usize, Encoding, Mode);
+
+    fn write<T: crate::tape::Write>(
+        &self,
+        tape: &mut T,
+        (size, encoding, mode): Self::Parameter,
+    ) -> Result<()> {
+        let value = encoding.encode(self, mode)?;
+        if value.len() != size {
+            raise!(
+                "found a string with {} bytes unequal to {}",
+                value.len(),
+                size,
+            );
+        }
+        tape.give_bytes(&value)
+    }
+}
+
+impl crate::walue::Write<'static> for String {
+    /// The number of bytes, the encoding, and the encoding mode.
+    type Parameter = (usize, Encoding, Mode);
+
+    #[inline]
+    fn write<T: crate::tape::Write>(&self, tape: &mut T, parameter: Self::Parameter) -> Result<()> {
+        tape.give_given(self.as_str(), parameter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Encoding, Mode};
+
+    #[test]
+    fn mac() {
+        let value = "Ärger über 10€ – naïve";
+        let bytes = Encoding::MacRoman.encode(value, Mode::Strict).unwrap();
+        assert_eq!(bytes[0], 0x80);
+        assert_eq!(
+            Encoding::MacRoman.decode(&bytes, Mode::Strict).unwrap(),
+            value
+        );
+        assert!(Encoding::MacRoman.encode("Ω ≠ Ж", Mode::Strict).is_err());
+        assert_eq!(
+            Encoding::MacRoman.encode("Ω ≠ Ж", Mode::Lossy).unwrap(),
+            b"\xBD \xAD ?",
+        );
+        let value = "Жизнь";
+        let bytes = Encoding::MacCyrillic.encode(value, Mode::Strict).unwrap();
+        assert_eq!(bytes.len(), 5);
+        assert_eq!(
+            Encoding::MacCyrillic.decode(&bytes, Mode::Strict).unwrap(),
+            value
+        );
+        let value = "سلام ١٢";
+        let bytes = Encoding::MacArabic.encode(value, Mode::Strict).unwrap();
+        assert_eq!(bytes, [0xD3, 0xE4, 0xC7, 0xE5, 0x20, 0xB1, 0xB2]);
+        assert_eq!(
+            Encoding::MacArabic.decode(&bytes, Mode::Strict).unwrap(),
+            value
+        );
+        let bytes = Encoding::MacFarsi.encode("۱۲", Mode::Strict).unwrap();
+        assert_eq!(bytes, [0xB1, 0xB2]);
+    }
+
+    #[test]
+    fn string() {
+        use crate::tape::{Read, Write};
+
+        let mut tape = std::io::Cursor::new(vec![]);
+        let parameter = (2, Encoding::Utf16BE, Mode::Strict);
+        tape.give_given("a", parameter).unwrap();
+        assert!(tape.give_given("ab", parameter).is_err());
+        tape.set_position(0);
+        assert_eq!(tape.take_given::<String>(parameter).unwrap(), "a");
+    }
+
+    #[test]
+    fn utf16be() {
+        let value = "a€𝄞";
+        let bytes = Encoding::Utf16BE.encode(value, Mode::Strict).unwrap();
+        assert_eq!(bytes, [0x00, 0x61, 0x20, 0xAC, 0xD8, 0x34, 0xDD, 0x1E]);
+        assert_eq!(
+            Encoding::Utf16BE.decode(&bytes, Mode::Strict).unwrap(),
+            value
+        );
+        let bytes = [0x00, 0x61, 0xD8, 0x34, 0x00, 0x62, 0x00];
+        assert!(Encoding::Utf16BE.decode(&bytes, Mode::Strict).is_err());
+        assert_eq!(
+            Encoding::Utf16BE.decode(&bytes, Mode::Lossy).unwrap(),
+            "a\u{FFFD}b\u{FFFD}",
+        );
+    }
+}
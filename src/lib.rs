@@ -3,13 +3,30 @@
 #[macro_use]
 mod macros;
 
+pub mod encoding;
+pub mod schema;
+pub mod sfnt;
 pub mod tape;
+pub mod ttcf;
 pub mod value;
+pub mod view;
+pub mod visit;
 pub mod walue;
 
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub mod serde;
+
+#[cfg(feature = "ttx")]
+pub mod ttx;
+
+mod counted;
 mod number;
+mod policy;
 
-pub use number::{q16, q32};
+pub use counted::{Counted, PascalString};
+pub use number::{q16, q32, UInt255, UIntBase128};
+pub use policy::Policy;
 
 /// An error.
 pub type Error = std::io::Error;
@@ -24,6 +41,15 @@ pub struct ErrorWithSource {
 /// A result.
 pub type Result<T> = std::io::Result<T>;
 
+/// An error caused by an unknown format.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnknownFormat {
+    /// The name of the type.
+    pub name: &'static str,
+    /// The format.
+    pub format: u64,
+}
+
 impl std::fmt::Display for ErrorWithSource {
     fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(formatter, "{}, due to {}", self.description, self.source)
@@ -36,3 +62,15 @@ impl std::error::Error for ErrorWithSource {
         Some(&self.source)
     }
 }
+
+impl std::fmt::Display for UnknownFormat {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            formatter,
+            "found an unknown format {} of type {}",
+            self.format, self.name,
+        )
+    }
+}
+
+impl std::error::Error for UnknownFormat {}
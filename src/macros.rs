@@ -1,8 +1,148 @@
+/// Implement a bit field.
+#[macro_export]
+macro_rules! bitfield {
+    ($(#[$attribute:meta])* pub $name:ident($type:ty) {
+        $($start:literal..$end:literal => $field:ident ($kind:ty) $([$setter:ident])?,)*
+    }) => (
+        $(#[$attribute])*
+        #[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
+        pub struct $name(pub $type);
+
+        $(
+            const _: () = assert!(
+                $start < $end && $end <= <$type>::BITS && $end - $start <= <$kind>::BITS,
+                concat!("found a malformed range of ", stringify!($name), "::", stringify!($field)),
+            );
+        )*
+
+        impl $name {
+            $(
+                #[inline]
+                pub fn $field(&self) -> $kind {
+                    bitfield!(@get self, $start, $end, $kind)
+                }
+
+                $(
+                    #[inline]
+                    pub fn $setter(&mut self, value: $kind) -> $crate::Result<()> {
+                        bitfield!(@set self, $start, $end, $kind, value, $name, $field)
+                    }
+                )?
+            )*
+        }
+
+        impl std::fmt::Debug for $name {
+            fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter
+                    .debug_struct(stringify!($name))
+                    $(.field(stringify!($field), &self.$field()))*
+                    .finish()
+            }
+        }
+
+        impl From<$name> for $type {
+            #[inline]
+            fn from(value: $name) -> $type {
+                value.0
+            }
+        }
+
+        impl From<$type> for $name {
+            #[inline]
+            fn from(value: $type) -> $name {
+                $name(value)
+            }
+        }
+
+        impl $crate::value::Read for $name {
+            #[inline]
+            fn read<T: $crate::tape::Read>(tape: &mut T) -> $crate::Result<Self> {
+                Ok($name(tape.take()?))
+            }
+        }
+
+        impl $crate::value::Write for $name {
+            #[inline]
+            fn write<T: $crate::tape::Write>(&self, tape: &mut T) -> $crate::Result<()> {
+                tape.give(&self.0)
+            }
+        }
+
+        impl $crate::value::Size for $name {
+            const SIZE: Option<usize> = <$type as $crate::value::Size>::SIZE;
+
+            #[inline]
+            fn size(&self) -> usize {
+                $crate::value::Size::size(&self.0)
+            }
+        }
+
+        impl $crate::value::FixedSize for $name {}
+
+        impl $crate::visit::Visit for $name {
+            fn visit(&self, visitor: &mut dyn $crate::visit::Visitor) {
+                $(let $field = self.$field();)*
+                visitor.table(
+                    stringify!($name),
+                    &[
+                        $(
+                            $crate::visit::Field {
+                                name: stringify!($field),
+                                kind: stringify!($kind),
+                                value: &$field,
+                            },
+                        )*
+                    ],
+                );
+            }
+        }
+
+        $crate::implement_serde! { @newtype $name($type) }
+        $crate::implement_ttx! { @bitfield $name { $($start..$end => $field ($kind),)* } }
+    );
+    (@get $this:ident, $start:literal, $end:literal, $kind:ty) => ({
+        let width: u32 = $end - $start;
+        let mask = (1u128 << width) - 1;
+        let value = ($this.0 as u128 >> $start) & mask;
+        if (<$kind>::MIN as i128) < 0 && value >> (width - 1) == 1 {
+            (value as i128 - (1i128 << width)) as $kind
+        } else {
+            value as $kind
+        }
+    });
+    (@set $this:ident, $start:literal, $end:literal, $kind:ty,
+     $value:ident, $name:ident, $field:ident) => ({
+        let width: u32 = $end - $start;
+        let mask = (1u128 << width) - 1;
+        let (minimum, maximum) = if (<$kind>::MIN as i128) < 0 {
+            (-(1i128 << (width - 1)), (1i128 << (width - 1)) - 1)
+        } else {
+            (0, mask as i128)
+        };
+        if ($value as i128) < minimum || ($value as i128) > maximum {
+            $crate::raise!(
+                concat!(
+                    "found a value {} exceeding the range of ",
+                    stringify!($name), "::", stringify!($field),
+                ),
+                $value,
+            );
+        }
+        let value = ($value as i128 as u128) & mask;
+        $this.0 = ((($this.0 as u128) & !(mask << $start)) | (value << $start)) as _;
+        Ok(())
+    });
+}
+
 /// Implement choices.
+///
+/// Ranges of values, given as `_ if start..=end => Variant,`, are supported only when there is
+/// a catch-all `_ => Other,` at the end.
 #[macro_export]
 macro_rules! choices {
     ($(#[$attribute:meta])* pub $name:ident($type:ty) {
         $($value:expr => $variant:ident,)*
+        $(_ if $start:literal..=$end:literal => $ranged:ident,)*
         _ => $other:ident,
     }) => (
         $(#[$attribute])*
@@ -10,13 +150,54 @@ macro_rules! choices {
         pub enum $name {
             #[default]
             $($variant,)*
+            $($ranged($type),)*
             $other($type),
         }
 
+        impl $name {
+            /// The variants without values.
+            pub const ALL: &'static [Self] = &[$($name::$variant,)*];
+
+            /// Return the name of the variant.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $($name::$variant => stringify!($variant),)*
+                    $($name::$ranged(_) => stringify!($ranged),)*
+                    $name::$other(_) => stringify!($other),
+                }
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = $crate::Error;
+
+            /// Parse the name of a variant without a value or a raw value.
+            fn from_str(value: &str) -> $crate::Result<Self> {
+                match value {
+                    $(stringify!($variant) => Ok($name::$variant),)*
+                    _ => match value.parse::<$type>() {
+                        Ok(value) => Ok(value.into()),
+                        _ => $crate::error!(
+                            concat!("found an unknown variant {:?} of type ", stringify!($name)),
+                            value,
+                        ),
+                    },
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            #[inline]
+            fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str(self.name())
+            }
+        }
+
         impl From<$name> for $type {
             fn from(value: $name) -> $type {
                 match value {
                     $($name::$variant => $value,)*
+                    $($name::$ranged(value) => value,)*
                     $name::$other(value) => value,
                 }
             }
@@ -26,6 +207,7 @@ macro_rules! choices {
             fn from(value: $type) -> $name {
                 match value {
                     $($value => $name::$variant,)*
+                    $(value @ $start..=$end => $name::$ranged(value),)*
                     value => $name::$other(value),
                 }
             }
@@ -33,22 +215,41 @@ macro_rules! choices {
 
         impl $crate::value::Read for $name {
             fn read<T: $crate::tape::Read>(tape: &mut T) -> $crate::Result<Self> {
-                match tape.take::<$type>()? {
-                    $($value => Ok($name::$variant),)*
-                    value => Ok($name::$other(value)),
-                }
+                Ok(tape.take::<$type>()?.into())
             }
         }
 
         impl $crate::value::Write for $name {
             fn write<T: $crate::tape::Write>(&self, tape: &mut T) -> $crate::Result<()> {
-                let value: $type = match self {
-                    $($name::$variant => $value,)*
-                    $name::$other(value) => *value,
-                };
-                tape.give(&value)
+                tape.give(&<$type>::from(*self))
             }
         }
+
+        impl $crate::visit::Visit for $name {
+            fn visit(&self, visitor: &mut dyn $crate::visit::Visitor) {
+                let value = <$type>::from(*self);
+                visitor.leaf($crate::visit::Leaf::Choice {
+                    variant: self.name(),
+                    value: &value,
+                });
+            }
+        }
+
+        $crate::implement_serde! {
+            @enum $name { $($variant,)* } { $($ranged($type),)* $other($type), }
+        }
+        $crate::implement_ttx! { @choices $name($type) { $($value => $variant,)* } _ => $other }
+
+        impl $crate::value::Size for $name {
+            const SIZE: Option<usize> = <$type as $crate::value::Size>::SIZE;
+
+            #[inline]
+            fn size(&self) -> usize {
+                $crate::value::Size::size(&<$type>::from(*self))
+            }
+        }
+
+        impl $crate::value::FixedSize for $name {}
     );
     ($(#[$attribute:meta])* pub $name:ident($type:ty) {
         $($value:expr => $variant:ident,)*
@@ -60,6 +261,40 @@ macro_rules! choices {
             $($variant = $value,)*
         }
 
+        impl $name {
+            /// The variants.
+            pub const ALL: &'static [Self] = &[$($name::$variant,)*];
+
+            /// Return the name of the variant.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $($name::$variant => stringify!($variant),)*
+                }
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = $crate::Error;
+
+            /// Parse the name of a variant.
+            fn from_str(value: &str) -> $crate::Result<Self> {
+                match value {
+                    $(stringify!($variant) => Ok($name::$variant),)*
+                    _ => $crate::error!(
+                        concat!("found an unknown variant {:?} of type ", stringify!($name)),
+                        value,
+                    ),
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            #[inline]
+            fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str(self.name())
+            }
+        }
+
         impl From<$name> for $type {
             #[inline]
             fn from(value: $name) -> $type {
@@ -110,6 +345,30 @@ macro_rules! choices {
                 tape.give(&value)
             }
         }
+
+        impl $crate::visit::Visit for $name {
+            fn visit(&self, visitor: &mut dyn $crate::visit::Visitor) {
+                let value = <$type>::from(*self);
+                visitor.leaf($crate::visit::Leaf::Choice {
+                    variant: self.name(),
+                    value: &value,
+                });
+            }
+        }
+
+        $crate::implement_serde! { @enum $name { $($variant,)* } {} }
+        $crate::implement_ttx! { @choices $name($type) { $($value => $variant,)* } }
+
+        impl $crate::value::Size for $name {
+            const SIZE: Option<usize> = <$type as $crate::value::Size>::SIZE;
+
+            #[inline]
+            fn size(&self) -> usize {
+                $crate::value::Size::size(&<$type>::from(*self))
+            }
+        }
+
+        impl $crate::value::FixedSize for $name {}
     );
     ($(#[$attribute:meta])* pub $name:ident($type:ty) {
         $($value:expr => $variant:ident($string:expr),)*
@@ -121,6 +380,56 @@ macro_rules! choices {
             $($variant = $value,)*
         }
 
+        impl $name {
+            /// The variants.
+            pub const ALL: &'static [Self] = &[$($name::$variant,)*];
+
+            /// Return the name of the variant.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $($name::$variant => stringify!($variant),)*
+                }
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = $crate::Error;
+
+            /// Parse the name of a variant.
+            fn from_str(value: &str) -> $crate::Result<Self> {
+                match value {
+                    $(stringify!($variant) => Ok($name::$variant),)*
+                    _ => $crate::error!(
+                        concat!("found an unknown variant {:?} of type ", stringify!($name)),
+                        value,
+                    ),
+                }
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = $crate::Error;
+
+            fn try_from(value: &str) -> $crate::Result<Self> {
+                $(
+                    if value == $string {
+                        return Ok($name::$variant);
+                    }
+                )*
+                $crate::error!(
+                    concat!("found an unknown string {:?} for type ", stringify!($name)),
+                    value,
+                )
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            #[inline]
+            fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str(self.name())
+            }
+        }
+
         impl From<$name> for $type {
             #[inline]
             fn from(value: $name) -> Self {
@@ -179,6 +488,30 @@ macro_rules! choices {
                 tape.give(&value)
             }
         }
+
+        impl $crate::visit::Visit for $name {
+            fn visit(&self, visitor: &mut dyn $crate::visit::Visitor) {
+                let value = <$type>::from(*self);
+                visitor.leaf($crate::visit::Leaf::Choice {
+                    variant: self.name(),
+                    value: &value,
+                });
+            }
+        }
+
+        $crate::implement_serde! { @enum $name { $($variant,)* } {} }
+        $crate::implement_ttx! { @choices $name($type) { $($value => $variant,)* } }
+
+        impl $crate::value::Size for $name {
+            const SIZE: Option<usize> = <$type as $crate::value::Size>::SIZE;
+
+            #[inline]
+            fn size(&self) -> usize {
+                $crate::value::Size::size(&<$type>::from(*self))
+            }
+        }
+
+        impl $crate::value::FixedSize for $name {}
     );
 }
 
@@ -246,17 +579,39 @@ macro_rules! error {
 }
 
 /// Implement flags.
+///
+/// A flag followed by `[CONSTANT, setter]` also gets a constant and a setter.
 #[macro_export]
 macro_rules! flags {
     ($(#[$attribute:meta])* pub $name:ident($type:ty) {
-        $($value:expr => $variant:ident,)*
+        $($value:expr => $variant:ident $([$constant:ident, $setter:ident])?,)*
+    }) => (
+        flags! {
+            @define
+            $(#[$attribute])* pub $name($type) {
+                $($value => $variant $([$constant, $setter])?,)*
+            }
+        }
+        flags!(@invalid pub $name($type));
+        flags!(@read pub $name($type));
+        flags!(@write pub $name($type));
+    );
+    ($(#[$attribute:meta])* pub $name:ident($type:ty) {
+        $($value:expr => $variant:ident $([$constant:ident, $setter:ident])?,)*
+        _ => reserved($reserved:expr),
     }) => (
-        flags!(@define $(#[$attribute])* pub $name($type) { $($value => $variant,)* });
+        flags! {
+            @define
+            $(#[$attribute])* pub $name($type) {
+                $($value => $variant $([$constant, $setter])?,)*
+            }
+        }
+        flags!(@reserved pub $name($type) { $reserved });
         flags!(@read pub $name($type));
         flags!(@write pub $name($type));
     );
     (@define $(#[$attribute:meta])* pub $name:ident($type:ty) {
-        $($value:expr => $variant:ident,)*
+        $($value:expr => $variant:ident $([$constant:ident, $setter:ident])?,)*
     }) => (
         $(#[$attribute])*
         #[derive(Clone, Copy, Default, Eq, PartialEq)]
@@ -266,14 +621,133 @@ macro_rules! flags {
             $(
                 #[inline]
                 pub fn $variant(&self) -> bool {
-                    self.0 & $value > 0
+                    self.0 & $value != 0
                 }
+
+                flags!(@setter $name, $value $(, [$constant, $setter])?);
             )*
+
+            /// Return flags with none set.
+            #[inline]
+            pub const fn empty() -> Self {
+                $name(0)
+            }
+
+            /// Return flags with all the named ones set.
+            #[inline]
+            pub const fn all() -> Self {
+                $name(0 $(| $value)*)
+            }
+
+            /// Check if all the flags set in another value are set.
+            #[inline]
+            pub const fn contains(&self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            /// Check if no flags are set.
+            #[inline]
+            pub const fn is_empty(&self) -> bool {
+                self.0 == 0
+            }
+
+            /// Set or clear the flags set in another value.
+            #[inline]
+            pub fn set(&mut self, other: Self, value: bool) {
+                if value {
+                    self.0 |= other.0;
+                } else {
+                    self.0 &= !other.0;
+                }
+            }
+
+            /// Iterate over the names of the set flags.
+            pub fn names(&self) -> impl Iterator<Item = &'static str> {
+                let value = self.0;
+                [$(($value, stringify!($variant)),)*]
+                    .into_iter()
+                    .filter(move |&(other, _): &($type, _)| value & other != 0)
+                    .map(|(_, name)| name)
+            }
         }
 
         impl std::fmt::Debug for $name {
             fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                write!(formatter, concat!(stringify!($name), "({:#b})"), self.0)
+                write!(formatter, concat!(stringify!($name), "("))?;
+                let mut first = true;
+                for name in self.names() {
+                    if !first {
+                        write!(formatter, " | ")?;
+                    }
+                    write!(formatter, "{}", name)?;
+                    first = false;
+                }
+                let rest = self.0 & !Self::all().0;
+                if rest != 0 || first {
+                    if !first {
+                        write!(formatter, " | ")?;
+                    }
+                    write!(formatter, "{:#x}", rest)?;
+                }
+                write!(formatter, ")")
+            }
+        }
+
+        impl std::ops::BitAnd for $name {
+            type Output = Self;
+
+            #[inline]
+            fn bitand(self, other: Self) -> Self {
+                $name(self.0 & other.0)
+            }
+        }
+
+        impl std::ops::BitAndAssign for $name {
+            #[inline]
+            fn bitand_assign(&mut self, other: Self) {
+                self.0 &= other.0;
+            }
+        }
+
+        impl std::ops::BitOr for $name {
+            type Output = Self;
+
+            #[inline]
+            fn bitor(self, other: Self) -> Self {
+                $name(self.0 | other.0)
+            }
+        }
+
+        impl std::ops::BitOrAssign for $name {
+            #[inline]
+            fn bitor_assign(&mut self, other: Self) {
+                self.0 |= other.0;
+            }
+        }
+
+        impl std::ops::BitXor for $name {
+            type Output = Self;
+
+            #[inline]
+            fn bitxor(self, other: Self) -> Self {
+                $name(self.0 ^ other.0)
+            }
+        }
+
+        impl std::ops::BitXorAssign for $name {
+            #[inline]
+            fn bitxor_assign(&mut self, other: Self) {
+                self.0 ^= other.0;
+            }
+        }
+
+        impl std::ops::Not for $name {
+            type Output = Self;
+
+            /// Complement the named flags and clear the rest.
+            #[inline]
+            fn not(self) -> Self {
+                $name(!self.0 & Self::all().0)
             }
         }
 
@@ -290,25 +764,128 @@ macro_rules! flags {
                 flags.0
             }
         }
-    );
-    (@read pub $name:ident($type:ty)) => (
-        impl $crate::value::Read for $name {
-            fn read<T: $crate::tape::Read>(tape: &mut T) -> $crate::Result<Self> {
-                let value = $name(tape.take::<$type>()?);
-                if value.is_invalid() {
-                    $crate::raise!(
-                        concat!(
-                            "found a malformed field of type ",
-                            stringify!($name),
-                            " with value {:?}",
-                        ),
-                        value,
-                    );
-                }
-                Ok(value)
+
+        impl $crate::visit::Visit for $name {
+            fn visit(&self, visitor: &mut dyn $crate::visit::Visitor) {
+                let names = self.names().collect::<Vec<_>>();
+                visitor.leaf($crate::visit::Leaf::Flags {
+                    names: &names,
+                    value: {
+                        let mut value = [0; 8];
+                        let bytes = self.0.to_be_bytes();
+                        value[(8 - bytes.len())..].copy_from_slice(&bytes);
+                        u64::from_be_bytes(value)
+                    },
+                    size: std::mem::size_of::<$type>(),
+                });
             }
         }
-    );
+
+        $crate::implement_serde! { @flags $name($type) { $($value => $variant,)* } }
+        $crate::implement_ttx! { @flags $name($type) }
+
+        impl $crate::value::Size for $name {
+            const SIZE: Option<usize> = <$type as $crate::value::Size>::SIZE;
+
+            #[inline]
+            fn size(&self) -> usize {
+                $crate::value::Size::size(&self.0)
+            }
+        }
+
+        impl $crate::value::FixedSize for $name {}
+    );
+    (@setter $name:ident, $value:expr) => ();
+    (@setter $name:ident, $value:expr, [$constant:ident, $setter:ident]) => (
+        pub const $constant: $name = $name($value);
+
+        #[inline]
+        pub fn $setter(&mut self, value: bool) {
+            self.set($name($value), value);
+        }
+    );
+    (@invalid pub $name:ident($type:ty)) => (
+        impl $name {
+            /// Check if any bit without a name is set.
+            #[inline]
+            pub fn is_invalid(&self) -> bool {
+                self.0 & !Self::all().0 != 0
+            }
+        }
+    );
+    (@reserved pub $name:ident($type:ty) { $reserved:expr }) => (
+        impl $name {
+            /// Check if any reserved bit is set.
+            #[inline]
+            pub fn is_invalid(&self) -> bool {
+                self.0 & ($reserved) != 0
+            }
+        }
+
+        impl $crate::walue::Read<'static> for $name {
+            type Parameter = $crate::Policy;
+
+            fn read<T: $crate::tape::Read>(
+                tape: &mut T,
+                policy: $crate::Policy,
+            ) -> $crate::Result<Self> {
+                let value = $name(tape.take::<$type>()?);
+                match policy {
+                    $crate::Policy::Reject if value.is_invalid() => $crate::error!(
+                        concat!(
+                            "found a malformed field of type ",
+                            stringify!($name),
+                            " with value {:?}",
+                        ),
+                        value,
+                    ),
+                    $crate::Policy::Clear => Ok($name(value.0 & !($reserved))),
+                    _ => Ok(value),
+                }
+            }
+        }
+
+        impl $crate::walue::Write<'static> for $name {
+            type Parameter = $crate::Policy;
+
+            fn write<T: $crate::tape::Write>(
+                &self,
+                tape: &mut T,
+                policy: $crate::Policy,
+            ) -> $crate::Result<()> {
+                match policy {
+                    $crate::Policy::Reject if self.is_invalid() => $crate::error!(
+                        concat!(
+                            "found a malformed field of type ",
+                            stringify!($name),
+                            " with value {:?}",
+                        ),
+                        self,
+                    ),
+                    $crate::Policy::Clear => tape.give(&(self.0 & !($reserved))),
+                    _ => tape.give(&self.0),
+                }
+            }
+        }
+    );
+    (@read pub $name:ident($type:ty)) => (
+        impl $crate::value::Read for $name {
+            fn read<T: $crate::tape::Read>(tape: &mut T) -> $crate::Result<Self> {
+                let value = $name(tape.take::<$type>()?);
+                if value.is_invalid() {
+                    $crate::raise!(
+                        concat!(
+                            "found a malformed field of type ",
+                            stringify!($name),
+                            " with value {:?}",
+                        ),
+                        value,
+                    );
+                }
+                Ok(value)
+            }
+        }
+    );
     (@write pub $name:ident($type:ty)) => (
         impl $crate::value::Write for $name {
             #[inline]
@@ -319,6 +896,605 @@ macro_rules! flags {
     );
 }
 
+/// Implement formats.
+///
+/// An optional trailing `_ => Other(Vec<u8>)` keeps an unknown format as the bytes remaining on
+/// the tape instead of failing.
+#[macro_export]
+macro_rules! formats {
+    (@define $(#[$attribute:meta])* pub $name:ident($type:ty) {
+        $($value:expr => $variant:ident($table:ty),)*
+        $(_ => $other:ident(Vec<u8>),)?
+    }) => (
+        $(#[$attribute])*
+        #[derive(Clone, Debug)]
+        pub enum $name {
+            $($variant($table),)*
+            $($other(Vec<u8>),)?
+        }
+
+        impl Default for $name {
+            #[inline]
+            fn default() -> Self {
+                formats!(@default $name $($variant)*)
+            }
+        }
+
+        $(
+            impl From<$table> for $name {
+                #[inline]
+                fn from(value: $table) -> Self {
+                    $name::$variant(value)
+                }
+            }
+        )*
+    );
+    (@read $name:ident($type:ty) {
+        $($value:expr => $variant:ident($table:ty),)*
+        $(_ => $other:ident(Vec<u8>),)?
+    }) => (
+        impl $crate::value::Read for $name {
+            fn read<T: $crate::tape::Read>(tape: &mut T) -> $crate::Result<Self> {
+                let format = tape.peek::<$type>()?;
+                match format {
+                    $($value => Ok($name::$variant(tape.take()?)),)*
+                    _ => formats!(@unknown $name, tape, format $(, $other)?),
+                }
+            }
+        }
+    );
+    (@write $name:ident($type:ty) {
+        $($value:expr => $variant:ident($table:ty),)*
+        $(_ => $other:ident(Vec<u8>),)?
+    }) => (
+        impl $crate::value::Write for $name {
+            fn write<T: $crate::tape::Write>(&self, tape: &mut T) -> $crate::Result<()> {
+                match self {
+                    $($name::$variant(value) => tape.give(value),)*
+                    $($name::$other(value) => tape.give_bytes(value),)?
+                }
+            }
+        }
+    );
+    (@size $name:ident($type:ty) {
+        $($value:expr => $variant:ident($table:ty),)*
+        $(_ => $other:ident(Vec<u8>),)?
+    }) => (
+        impl $crate::value::Size for $name {
+            fn size(&self) -> usize {
+                match self {
+                    $($name::$variant(value) => $crate::value::Size::size(value),)*
+                    $($name::$other(value) => value.len(),)?
+                }
+            }
+        }
+    );
+    (@visit $name:ident { $($variant:ident,)* $(_ => $other:ident,)? }) => (
+        impl $crate::visit::Visit for $name {
+            fn visit(&self, visitor: &mut dyn $crate::visit::Visitor) {
+                match self {
+                    $($name::$variant(value) => value.visit(visitor),)*
+                    $($name::$other(value) => value.visit(visitor),)?
+                }
+            }
+        }
+    );
+    (@default $name:ident $variant:ident $($rest:ident)*) => (
+        $name::$variant(Default::default())
+    );
+    (@unknown $name:ident, $tape:ident, $format:ident) => (
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            $crate::UnknownFormat {
+                name: stringify!($name),
+                format: $format as u64,
+            },
+        ))
+    );
+    (@unknown $name:ident, $tape:ident, $format:ident, $other:ident) => ({
+        let size = $tape.remaining()? as usize;
+        Ok($name::$other($tape.take_bytes(size)?))
+    });
+    (@flag serde [serde $($flag:tt)*] { $($yes:tt)* } { $($no:tt)* }) => ($($yes)*);
+    (@flag size [size $($flag:tt)*] { $($yes:tt)* } { $($no:tt)* }) => ($($yes)*);
+    (@flag ttx [ttx $($flag:tt)*] { $($yes:tt)* } { $($no:tt)* }) => ($($yes)*);
+    (@flag visit [visit $($flag:tt)*] { $($yes:tt)* } { $($no:tt)* }) => ($($yes)*);
+    (@flag write [write $($flag:tt)*] { $($yes:tt)* } { $($no:tt)* }) => ($($yes)*);
+    (@flag $name:ident [$other:tt $($flag:tt)*] $yes:tt $no:tt) => (
+        formats!(@flag $name [$($flag)*] $yes $no);
+    );
+    (@flag $name:ident [] { $($yes:tt)* } { $($no:tt)* }) => ($($no)*);
+
+    (@formats [$($flag:tt)*] @serde $($rest:tt)*) => (
+        formats!(@formats [$($flag)* serde] $($rest)*);
+    );
+    (@formats [$($flag:tt)*] @size $($rest:tt)*) => (
+        formats!(@formats [$($flag)* size] $($rest)*);
+    );
+    (@formats [$($flag:tt)*] @ttx $($rest:tt)*) => (
+        formats!(@formats [$($flag)* ttx] $($rest)*);
+    );
+    (@formats [$($flag:tt)*] @visit $($rest:tt)*) => (
+        formats!(@formats [$($flag)* visit] $($rest)*);
+    );
+    (@formats [$($flag:tt)*] @write $($rest:tt)*) => (
+        formats!(@formats [$($flag)* write] $($rest)*);
+    );
+    (@formats [$($flag:tt)*] @$other:tt $($rest:tt)*) => (
+        compile_error!(concat!("found an unknown flag @", stringify!($other)));
+    );
+    (@formats [$($flag:tt)*] $(#[$attribute:meta])* pub $name:ident($type:ty) {
+        $($value:expr => $variant:ident($table:ty),)*
+        $(_ => $other:ident(Vec<u8>),)?
+    }) => (
+        formats! {
+            @define
+            $(#[$attribute])* pub $name($type) {
+                $($value => $variant($table),)*
+                $(_ => $other(Vec<u8>),)?
+            }
+        }
+        formats! {
+            @read
+            $name($type) {
+                $($value => $variant($table),)*
+                $(_ => $other(Vec<u8>),)?
+            }
+        }
+        formats! {
+            @flag write [$($flag)*] {
+                formats! {
+                    @write
+                    $name($type) {
+                        $($value => $variant($table),)*
+                        $(_ => $other(Vec<u8>),)?
+                    }
+                }
+            } {}
+        }
+        formats! {
+            @flag size [$($flag)*] {
+                formats! {
+                    @size
+                    $name($type) {
+                        $($value => $variant($table),)*
+                        $(_ => $other(Vec<u8>),)?
+                    }
+                }
+            } {}
+        }
+        formats! {
+            @flag ttx [$($flag)*] {
+                $crate::implement_ttx! {
+                    @formats $name($type) { $($value => $variant($table),)* } $(_ => $other)?
+                }
+            } {}
+        }
+        formats! {
+            @flag visit [$($flag)*] {
+                formats!(@visit $name { $($variant,)* $(_ => $other,)? });
+            } {}
+        }
+        formats! {
+            @flag serde [$($flag)*] {
+                $crate::implement_serde! { @enum $name {} { $($variant,)* $($other,)? } }
+            } {}
+        }
+    );
+    ($($token:tt)*) => (formats! { @formats [] $($token)* });
+}
+
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! implement_serde {
+    (@enum $name:ident { $($unit:ident,)* } { $($newtype:ident $(($type:ty))?,)* }) => (
+        impl $crate::serde::Serialize for $name {
+            fn serialize<T>(&self, serializer: T) -> std::result::Result<T::Ok, T::Error>
+            where
+                T: $crate::serde::Serializer,
+            {
+                const VARIANTS: &[&str] = &[$(stringify!($unit),)* $(stringify!($newtype),)*];
+
+                fn index(name: &str) -> u32 {
+                    VARIANTS.iter().position(|&other| other == name).unwrap_or_default() as u32
+                }
+
+                match self {
+                    $(
+                        $name::$unit => {
+                            let name = stringify!($unit);
+                            serializer.serialize_unit_variant(stringify!($name), index(name), name)
+                        }
+                    )*
+                    $(
+                        $name::$newtype(value) => {
+                            let name = stringify!($newtype);
+                            serializer.serialize_newtype_variant(
+                                stringify!($name),
+                                index(name),
+                                name,
+                                value,
+                            )
+                        }
+                    )*
+                }
+            }
+        }
+
+        impl<'l> $crate::serde::Deserialize<'l> for $name {
+            fn deserialize<T>(deserializer: T) -> std::result::Result<Self, T::Error>
+            where
+                T: $crate::serde::Deserializer<'l>,
+            {
+                use $crate::serde::de::{EnumAccess, Error, VariantAccess};
+
+                const VARIANTS: &[&str] = &[$(stringify!($unit),)* $(stringify!($newtype),)*];
+
+                struct Visitor;
+
+                impl<'l> $crate::serde::de::Visitor<'l> for Visitor {
+                    type Value = $name;
+
+                    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        formatter.write_str(concat!("a variant of ", stringify!($name)))
+                    }
+
+                    fn visit_enum<T>(self, data: T) -> std::result::Result<$name, T::Error>
+                    where
+                        T: EnumAccess<'l>,
+                    {
+                        let (variant, access): (String, _) = data.variant()?;
+                        match variant.as_str() {
+                            $(
+                                stringify!($unit) => {
+                                    access.unit_variant()?;
+                                    Ok($name::$unit)
+                                }
+                            )*
+                            $(
+                                stringify!($newtype) => $crate::implement_serde!(
+                                    @variant $name::$newtype, access, T $(($type))?
+                                ),
+                            )*
+                            _ => Err(T::Error::unknown_variant(&variant, VARIANTS)),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_enum(stringify!($name), VARIANTS, Visitor)
+            }
+        }
+    );
+    (@flags $name:ident($type:ty) { $($value:expr => $variant:ident,)* }) => (
+        impl $crate::serde::Serialize for $name {
+            fn serialize<T>(&self, serializer: T) -> std::result::Result<T::Ok, T::Error>
+            where
+                T: $crate::serde::Serializer,
+            {
+                use $crate::serde::ser::SerializeSeq;
+
+                let mut sequence = serializer.serialize_seq(None)?;
+                $(
+                    if self.$variant() {
+                        sequence.serialize_element(stringify!($variant))?;
+                    }
+                )*
+                let known: $type = 0 $(| $value)*;
+                if self.0 & !known != 0 {
+                    let mut value = [0; 8];
+                    let bytes = (self.0 & !known).to_be_bytes();
+                    value[(8 - bytes.len())..].copy_from_slice(&bytes);
+                    sequence.serialize_element(&u64::from_be_bytes(value))?;
+                }
+                sequence.end()
+            }
+        }
+
+        impl<'l> $crate::serde::Deserialize<'l> for $name {
+            fn deserialize<T>(deserializer: T) -> std::result::Result<Self, T::Error>
+            where
+                T: $crate::serde::Deserializer<'l>,
+            {
+                use $crate::serde::de::Error;
+
+                const VARIANTS: &[&str] = &[$(stringify!($variant),)*];
+
+                let mut value: $type = 0;
+                for flag in Vec::<$crate::serde::Flag>::deserialize(deserializer)? {
+                    match flag {
+                        $(
+                            $crate::serde::Flag::Name(name) if name == stringify!($variant) => {
+                                value |= $value;
+                            }
+                        )*
+                        $crate::serde::Flag::Name(name) => {
+                            return Err(T::Error::unknown_variant(&name, VARIANTS));
+                        }
+                        $crate::serde::Flag::Bits(bits) => {
+                            let size = 8 * std::mem::size_of::<$type>() as u32;
+                            if bits.checked_shr(size).unwrap_or_default() == 0
+                                || <$type>::try_from(bits as i64).is_ok()
+                            {
+                                value |= bits as $type;
+                            } else {
+                                return Err(T::Error::custom(format!(
+                                    concat!("found bits {:#x} exceeding type ", stringify!($name)),
+                                    bits,
+                                )));
+                            }
+                        }
+                    }
+                }
+                Ok($name(value))
+            }
+        }
+    );
+    (@newtype $name:ident($type:ty)) => (
+        impl $crate::serde::Serialize for $name {
+            #[inline]
+            fn serialize<T>(&self, serializer: T) -> std::result::Result<T::Ok, T::Error>
+            where
+                T: $crate::serde::Serializer,
+            {
+                $crate::serde::Serialize::serialize(&self.0, serializer)
+            }
+        }
+
+        impl<'l> $crate::serde::Deserialize<'l> for $name {
+            #[inline]
+            fn deserialize<T>(deserializer: T) -> std::result::Result<Self, T::Error>
+            where
+                T: $crate::serde::Deserializer<'l>,
+            {
+                <$type as $crate::serde::Deserialize>::deserialize(deserializer).map($name)
+            }
+        }
+    );
+    (@table $name:ident { $($field:ident ($type:ty),)* }) => (
+        impl $crate::serde::Serialize for $name {
+            fn serialize<T>(&self, serializer: T) -> std::result::Result<T::Ok, T::Error>
+            where
+                T: $crate::serde::Serializer,
+            {
+                use $crate::serde::ser::SerializeStruct;
+
+                const FIELDS: &[&str] = &[$(stringify!($field),)*];
+
+                let mut table = serializer.serialize_struct(stringify!($name), FIELDS.len())?;
+                $(table.serialize_field(stringify!($field), &self.$field)?;)*
+                table.end()
+            }
+        }
+
+        impl<'l> $crate::serde::Deserialize<'l> for $name {
+            fn deserialize<T>(deserializer: T) -> std::result::Result<Self, T::Error>
+            where
+                T: $crate::serde::Deserializer<'l>,
+            {
+                use $crate::serde::de::{Error, MapAccess, SeqAccess};
+
+                const FIELDS: &[&str] = &[$(stringify!($field),)*];
+
+                struct Visitor;
+
+                impl<'l> $crate::serde::de::Visitor<'l> for Visitor {
+                    type Value = $name;
+
+                    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        formatter.write_str(concat!("struct ", stringify!($name)))
+                    }
+
+                    #[allow(unused_assignments, unused_mut, unused_variables)]
+                    fn visit_seq<T>(self, mut data: T) -> std::result::Result<$name, T::Error>
+                    where
+                        T: SeqAccess<'l>,
+                    {
+                        let mut index = 0;
+                        Ok($name {
+                            $(
+                                $field: match data.next_element()? {
+                                    Some(value) => {
+                                        index += 1;
+                                        value
+                                    }
+                                    _ => return Err(T::Error::invalid_length(index, &self)),
+                                },
+                            )*
+                        })
+                    }
+
+                    #[allow(unused_mut)]
+                    fn visit_map<T>(self, mut data: T) -> std::result::Result<$name, T::Error>
+                    where
+                        T: MapAccess<'l>,
+                    {
+                        struct Fields {
+                            $($field: Option<$type>,)*
+                        }
+
+                        let mut fields = Fields { $($field: None,)* };
+                        while let Some(key) = data.next_key::<String>()? {
+                            match key.as_str() {
+                                $(
+                                    stringify!($field) => {
+                                        if fields.$field.is_some() {
+                                            let name = stringify!($field);
+                                            return Err(T::Error::duplicate_field(name));
+                                        }
+                                        fields.$field = Some(data.next_value()?);
+                                    }
+                                )*
+                                _ => return Err(T::Error::unknown_field(&key, FIELDS)),
+                            }
+                        }
+                        Ok($name {
+                            $(
+                                $field: match fields.$field {
+                                    Some(value) => value,
+                                    _ => return Err(T::Error::missing_field(stringify!($field))),
+                                },
+                            )*
+                        })
+                    }
+                }
+
+                deserializer.deserialize_struct(stringify!($name), FIELDS, Visitor)
+            }
+        }
+    );
+    (@variant $name:ident::$variant:ident, $access:ident, $data:ident) => (
+        Ok($name::$variant($access.newtype_variant()?))
+    );
+    (@variant $name:ident::$variant:ident, $access:ident, $data:ident ($type:ty)) => ({
+        let value: $type = $access.newtype_variant()?;
+        match $name::from(value) {
+            value @ $name::$variant(_) => Ok(value),
+            _ => Err($data::Error::custom(format!(
+                concat!(
+                    "found a value {} outside the range of ",
+                    stringify!($name), "::", stringify!($variant),
+                ),
+                value,
+            ))),
+        }
+    });
+}
+
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! implement_serde {
+    ($($token:tt)*) => {};
+}
+
+#[cfg(feature = "ttx")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! implement_ttx {
+    (@bitfield $name:ident {
+        $($start:literal..$end:literal => $field:ident ($kind:ty),)*
+    }) => (
+        impl $crate::ttx::Load for $name {
+            fn load(element: &$crate::ttx::Element) -> $crate::Result<Self> {
+                let mut this = $name::default();
+                $({
+                    let name = $crate::ttx::rename(stringify!($field));
+                    let elements = element.children_named(&name).collect::<Vec<_>>();
+                    let value = <$kind as $crate::ttx::Load>::load_all(&elements)?;
+                    let result: $crate::Result<()> = $crate::bitfield!(
+                        @set this, $start, $end, $kind, value, $name, $field
+                    );
+                    result?;
+                })*
+                Ok(this)
+            }
+        }
+    );
+    (@choices $name:ident($type:ty) { $($value:expr => $variant:ident,)* } _ => $other:ident) => (
+        impl $crate::ttx::Load for $name {
+            #[inline]
+            fn load(element: &$crate::ttx::Element) -> $crate::Result<Self> {
+                <$type as $crate::ttx::Load>::load(element).map(Into::into)
+            }
+        }
+    );
+    (@choices $name:ident($type:ty) { $($value:expr => $variant:ident,)* }) => (
+        impl $crate::ttx::Load for $name {
+            fn load(element: &$crate::ttx::Element) -> $crate::Result<Self> {
+                match <$type as $crate::ttx::Load>::load(element)? {
+                    $($value => Ok($name::$variant),)*
+                    value => $crate::error!(
+                        concat!(
+                            "found a malformed element {} of type ",
+                            stringify!($name),
+                            " with value {:?}",
+                        ),
+                        element.name,
+                        value,
+                    ),
+                }
+            }
+        }
+    );
+    (@flags $name:ident($type:ty)) => (
+        impl $crate::ttx::Load for $name {
+            fn load(element: &$crate::ttx::Element) -> $crate::Result<Self> {
+                let mut value = [0; std::mem::size_of::<$type>()];
+                let bits = $crate::ttx::load_flags(element)?.to_be_bytes();
+                let (head, tail) = bits.split_at(bits.len() - value.len());
+                if head.iter().any(|&byte| byte != 0) {
+                    $crate::raise!(
+                        concat!("found flags exceeding ", stringify!($name), " in element {}"),
+                        element.name,
+                    );
+                }
+                value.copy_from_slice(tail);
+                Ok($name(<$type>::from_be_bytes(value)))
+            }
+        }
+    );
+    (@formats $name:ident($type:ty) {
+        $($value:expr => $variant:ident($table:ty),)*
+    } $(_ => $other:ident)?) => (
+        impl $crate::ttx::Load for $name {
+            fn load(element: &$crate::ttx::Element) -> $crate::Result<Self> {
+                let mut format = None;
+                $(
+                    let fields = <$table as $crate::schema::Schema>::FIELDS;
+                    if let Some(field) = fields.first() {
+                        let name = $crate::ttx::rename(field.name);
+                        let child = element.children_named(&name).next();
+                        if let Some(child) = child {
+                            let value = <$type as $crate::ttx::Load>::load(child)?;
+                            if value == $value {
+                                return <$table as $crate::ttx::Load>::load(element).map($name::$variant);
+                            }
+                            format = Some(value);
+                        }
+                    }
+                )*
+                match format {
+                    Some(format) => $crate::error!(
+                        concat!("found an unknown format {} of type ", stringify!($name)),
+                        format,
+                    ),
+                    _ => $crate::error!("found no format in element {}", element.name),
+                }
+            }
+
+            $(
+                fn load_all(elements: &[&$crate::ttx::Element]) -> $crate::Result<Self> {
+                    match elements {
+                        [element] if !element.children.is_empty() => Self::load(element),
+                        _ => Ok($name::$other($crate::ttx::Load::load_all(elements)?)),
+                    }
+                }
+            )?
+        }
+    );
+    (@table $name:ident { $($field:ident ($type:ty),)* }) => (
+        impl $crate::ttx::Load for $name {
+            fn load(element: &$crate::ttx::Element) -> $crate::Result<Self> {
+                let mut table: $name = $name::default();
+                $({
+                    let name = $crate::ttx::rename(stringify!($field));
+                    let elements = element.children_named(&name).collect::<Vec<_>>();
+                    table.$field = <$type as $crate::ttx::Load>::load_all(&elements)?;
+                })*
+                Ok(table)
+            }
+        }
+    );
+}
+
+#[cfg(not(feature = "ttx"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! implement_ttx {
+    ($($token:tt)*) => {};
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! jump_take {
@@ -413,80 +1589,84 @@ macro_rules! raise {
 }
 
 /// Implement a table.
+///
+/// A field present only under a condition is written as `field (T) if |this| { ... }`, where
+/// `this` is the table read so far. The binding is named by the caller because macro hygiene
+/// would hide one introduced by the macro itself from the condition.
 #[macro_export]
 macro_rules! table {
-    ($(#[$attribute:meta])* pub $name:ident {
-        $($field:ident ($($type:tt)+) $(= $value:block)* $(|$($argument:tt),+| $body:block)*,)*
+    (@define $(#[$attribute:meta])* pub $name:ident {
+        $($field:ident ($($type:tt)+) [$($condition:tt)?],)*
     }) => (
-        table! {
-            @define
-            $(#[$attribute])* pub $name { $($field ($($type)+),)* }
-        }
-        table! {
-            @read
-            pub $name { $($field ($($type)+) [$($value)*] $(|$($argument),+| $body)*,)* }
-        }
-    );
-    (@position $(#[$attribute:meta])* pub $name:ident {
-        $($field:ident ($($type:tt)+) $(= $value:block)* $(|$($argument:tt),+| $body:block)*,)*
-    }) => (
-        table! {
-            @define
-            $(#[$attribute])* pub $name { $($field ($($type)+),)* }
-        }
-        table! {
-            @read @position
-            pub $name { $($field ($($type)+) [$($value)*] $(|$($argument),+| $body)*,)* }
-        }
-    );
-    (@position @write $(#[$attribute:meta])* pub $name:ident {
-        $($field:ident ($($type:tt)+) $(= $value:block)* $(|$($argument:tt),+| $body:block)*,)*
-    }) => (
-        table! {
-            @define
-            $(#[$attribute])* pub $name { $($field ($($type)+),)* }
-        }
-        table! {
-            @read @position
-            pub $name { $($field ($($type)+) [$($value)*] $(|$($argument),+| $body)*,)* }
-        }
-        table! {
-            @write
-            pub $name { $($field ($($type)+) [],)* }
-        }
+        $(#[$attribute])*
+        #[derive(Clone, Debug, Default)]
+        pub struct $name { $(pub $field: table!(@type [$($type)+] [$($condition)?]),)* }
     );
-    (@write $(#[$attribute:meta])* pub $name:ident {
-        $($field:ident ($($type:tt)+) $(= $value:block)* $(|$($argument:tt),+| $body:block)*,)*
+    (@type [$($type:tt)+] []) => ($($type)+);
+    (@type [$($type:tt)+] [$condition:tt]) => (Option<$($type)+>);
+    (@kind [$($type:tt)+] []) => (stringify!($($type)+));
+    (@kind [$($type:tt)+] [$condition:tt]) => (concat!("Option<", stringify!($($type)+), ">"));
+
+    (@schema pub $name:ident {
+        $(
+            $field:ident ($($type:tt)+)
+            [$($value:block)?] [$($count:ident)?] [$($condition:tt)?] [$($body:block)?],
+        )*
     }) => (
-        table! {
-            @define
-            $(#[$attribute])* pub $name { $($field ($($type)+),)* }
-        }
-        table! {
-            @read
-            pub $name { $($field ($($type)+) [$($value)*] $(|$($argument),+| $body)*,)* }
-        }
-        table! {
-            @write
-            pub $name { $($field ($($type)+) [],)* }
+        impl $crate::schema::Schema for $name {
+            const NAME: &'static str = stringify!($name);
+
+            const FIELDS: &'static [$crate::schema::Field] = &$crate::schema::locate([
+                $(
+                    $crate::schema::Field {
+                        name: stringify!($field),
+                        kind: table!(@kind [$($type)+] [$($condition)?]),
+                        value: table!(@schema value [$($value)?]),
+                        computed: table!(@schema computed [$($body)?]),
+                        count: table!(@schema count [$($count)?]),
+                        condition: table!(@schema condition [$($condition)?]),
+                        size: table!(
+                            @schema size [$($type)+] [$($count)? $($condition)? $($body)?]
+                        ),
+                        offset: None,
+                    },
+                )*
+            ]);
         }
+
+        const _: () = $crate::schema::check(<$name as $crate::schema::Schema>::FIELDS);
     );
-    (@define $(#[$attribute:meta])* pub $name:ident { $($field:ident ($type:ty),)* }) => (
-        $(#[$attribute])*
-        #[derive(Clone, Debug, Default)]
-        pub struct $name { $(pub $field: $type,)* }
+    (@schema value []) => (None);
+    (@schema value [$value:block]) => (Some($crate::schema::unwrap(stringify!($value))));
+    (@schema computed []) => (false);
+    (@schema computed [$body:block]) => (true);
+    (@schema count []) => (None);
+    (@schema count [$count:ident]) => (Some(stringify!($count)));
+    (@schema condition []) => (None);
+    (@schema condition [{ |$argument:tt| $condition:block }]) => (
+        Some($crate::schema::unwrap(stringify!($condition)))
     );
+    (@schema size [$($type:tt)+] [$($source:tt)+]) => (None);
+    (@schema size [$($type:tt)+] []) => ({
+        #[allow(unused_imports)]
+        use $crate::schema::Fallback;
+        $crate::schema::Probe::<$($type)+>::SIZE
+    });
 
     (@read pub $name:ident {
-        $($field:ident ($type:ty) [$($value:block)*] $(|$($argument:tt),+| $body:block)*,)*
+        $($field:ident ($type:ty) [$($value:block)?] [$($count:ident)?] [$($condition:tt)?]
+          $(|$($argument:tt),+| $body:block)?,)*
     }) => (
         impl $crate::value::Read for $name {
             fn read<T: $crate::tape::Read>(tape: &mut T) -> $crate::Result<Self> {
                 let mut table: $name = $name::default();
                 $({
                     let value = table!(
-                        @read $name, table.$field, tape [] [$type] [$($value)*]
-                        $(|$($argument),+| $body)*
+                        @option [&table] [$($condition)?]
+                        table!(
+                            @read $name, table.$field, tape [] [$type] [$($value)?] [$($count)?]
+                            $(|$($argument),+| $body)?
+                        )
                     );
                     #[allow(forgetting_copy_types)]
                     std::mem::forget(std::mem::replace(&mut table.$field, value));
@@ -496,7 +1676,8 @@ macro_rules! table {
         }
     );
     (@read @position pub $name:ident {
-        $($field:ident ($type:ty) [$($value:block)*] $(|$($argument:tt),+| $body:block)*,)*
+        $($field:ident ($type:ty) [$($value:block)?] [$($count:ident)?] [$($condition:tt)?]
+          $(|$($argument:tt),+| $body:block)?,)*
     }) => (
         impl $crate::value::Read for $name {
             fn read<T: $crate::tape::Read>(tape: &mut T) -> $crate::Result<Self> {
@@ -504,8 +1685,11 @@ macro_rules! table {
                 let mut table: $name = $name::default();
                 $({
                     let value = table!(
-                        @read $name, table.$field, tape [position] [$type] [$($value)*]
-                        $(|$($argument),+| $body)*
+                        @option [&table] [$($condition)?]
+                        table!(
+                            @read $name, table.$field, tape [position] [$type]
+                            [$($value)?] [$($count)?] $(|$($argument),+| $body)?
+                        )
                     );
                     #[allow(forgetting_copy_types, clippy::forget_non_drop)]
                     std::mem::forget(std::mem::replace(&mut table.$field, value));
@@ -515,11 +1699,16 @@ macro_rules! table {
         }
     );
 
-    (@read $name:ident, $this:ident . $field:ident, $tape:ident [$($position:tt)*] [$type:ty] []) => (
+    (@read $name:ident, $this:ident . $field:ident, $tape:ident [$($position:tt)*] [$type:ty]
+     [] []) => (
         $tape.take()?
     );
     (@read $name:ident, $this:ident . $field:ident, $tape:ident [$($position:tt)*] [$type:ty]
-     [$value:block]) => ({
+     [] [$count:ident]) => (
+        $tape.take_given($this.$count as usize)?
+    );
+    (@read $name:ident, $this:ident . $field:ident, $tape:ident [$($position:tt)*] [$type:ty]
+     [$value:block] []) => ({
         let value = $tape.take()?;
         if value != $value {
             $crate::raise!(
@@ -534,7 +1723,7 @@ macro_rules! table {
         }
         value
     });
-    (@read $name:ident, $this:ident . $field:ident, $tape:ident [] [$type:ty] []
+    (@read $name:ident, $this:ident . $field:ident, $tape:ident [] [$type:ty] [] []
      |$this_:tt, $tape_:tt| $body:block) => ({
         #[inline]
         fn read<T: $crate::tape::Read>(
@@ -543,7 +1732,7 @@ macro_rules! table {
         ) -> $crate::Result<$type> $body
         read(&$this, $tape)?
     });
-    (@read $name:ident, $this:ident . $field:ident, $tape:ident [$position:ident] [$type:ty] []
+    (@read $name:ident, $this:ident . $field:ident, $tape:ident [$position:ident] [$type:ty] [] []
      |$this_:tt, $tape_:tt, $position_:tt| $body:block) => ({
         #[inline]
         fn read<T: $crate::tape::Read>(
@@ -553,30 +1742,574 @@ macro_rules! table {
         ) -> $crate::Result<$type> $body
         read(&$this, $tape, $position)?
     });
+    (@option [$($this:tt)+] [] $($read:tt)+) => ($($read)+);
+    (@option [$($this:tt)+] [$condition:tt] $($read:tt)+) => (
+        if table!(@condition [$($this)+] $condition) {
+            Some($($read)+)
+        } else {
+            None
+        }
+    );
 
     (@write pub $name:ident {
-        $($field:ident ($($type:tt)+) [],)*
+        $($field:ident ($($type:tt)+) [$($count:ident)?] [$($condition:tt)?] [$($closure:tt)*],)*
     }) => (
         impl $crate::value::Write for $name {
             fn write<T: $crate::tape::Write>(&self, tape: &mut T) -> $crate::Result<()> {
-                $(table!(@write $name, self.$field, tape [] [$($type)+]);)*
+                $(#[allow(unused_variables)] let $field = &self.$field;)*
+                $(table!(@count $name, self.$field [$($count)?]);)*
+                $(
+                    table!(
+                        @write $name, self.$field, tape [$($condition)?] [$($closure)*] [$($type)+]
+                    );
+                )*
                 Ok(())
             }
         }
     );
-    (@write $name:ident, $this:ident . $field:ident, $tape:ident [] [Vec<u8>]) => (
-        $tape.give_bytes(&*$this.$field)?;
+    (@size pub $name:ident {
+        $($field:ident ($($type:tt)+) [$($condition:tt)?] [$($closure:tt)*],)*
+    }) => (
+        impl $crate::value::Size for $name {
+            const SIZE: Option<usize> = {
+                let size = Some(0);
+                $(
+                    let size = $crate::value::add(
+                        size,
+                        table!(@size [$($condition)?] [$($closure)*] [$($type)+]),
+                    );
+                )*
+                size
+            };
+
+            fn size(&self) -> usize {
+                0 $(+ table!(@size $name, self.$field [$($condition)?] [$($closure)*] [$($type)+]))*
+            }
+        }
+    );
+    (@write $name:ident, $this:ident . $field:ident, $tape:ident [] [] [Vec<u8>]) => (
+        $tape.give_bytes($field)?;
+    );
+    (@write $name:ident, $this:ident . $field:ident, $tape:ident [] [] [Vec<$type:ty>]) => (
+        $tape.give(&$field[..])?;
+    );
+    (@write $name:ident, $this:ident . $field:ident, $tape:ident [] [] [$type:ty]) => (
+        $tape.give($field)?;
+    );
+    (@write $name:ident, $this:ident . $field:ident, $tape:ident []
+     [|$this_:tt, $tape_:tt| $body:block] [$type:ty]) => ({
+        #[inline]
+        fn write<T: $crate::tape::Write>($this_: &$name, $tape_: &mut T) -> $crate::Result<()> $body
+        write($this, $tape)?;
+    });
+    (@write $name:ident, $this:ident . $field:ident, $tape:ident [$condition:tt] [] [$type:ty]) => (
+        table!(@present $name, $this.$field [$condition]);
+        if let Some(value) = $field {
+            $tape.give(value)?;
+        }
+    );
+    (@write $name:ident, $this:ident . $field:ident, $tape:ident [$condition:tt]
+     [$($closure:tt)+] [$type:ty]) => (
+        table!(@present $name, $this.$field [$condition]);
+        if $this.$field.is_some() {
+            table!(@write $name, $this.$field, $tape [] [$($closure)+] [$type]);
+        }
+    );
+
+    (@present $name:ident, $this:ident . $field:ident [$condition:tt]) => (
+        if $this.$field.is_some() != table!(@condition [$this] $condition) {
+            $crate::raise!(concat!(
+                "found a malformed field ",
+                stringify!($name), "::", stringify!($field),
+                " whose presence disagrees with its condition",
+            ));
+        }
     );
-    (@write $name:ident, $this:ident . $field:ident, $tape:ident [] [Vec<$type:ty>]) => (
-        $tape.give(&*$this.$field)?;
+
+    (@condition [$($this:tt)+] { |$argument:tt| $condition:block }) => ({
+        let $argument = $($this)+;
+        $condition
+    });
+
+    (@count $name:ident, $this:ident . $field:ident []) => ();
+    (@count $name:ident, $this:ident . $field:ident [$count:ident]) => (
+        let $count = &{
+            #[inline]
+            fn convert<T: std::convert::TryFrom<usize>>(_: &T, value: usize) -> Option<T> {
+                T::try_from(value).ok()
+            }
+            match convert(&$this.$count, $this.$field.len()) {
+                Some(value) => value,
+                _ => $crate::raise!(
+                    concat!(
+                        "found a malformed field ",
+                        stringify!($name), "::", stringify!($field),
+                        " with {} elements exceeding the range of ",
+                        stringify!($name), "::", stringify!($count),
+                    ),
+                    $this.$field.len(),
+                ),
+            }
+        };
     );
-    (@write $name:ident, $this:ident . $field:ident, $tape:ident [] [$type:ty]) => (
-        $tape.give(&$this.$field)?;
+
+    (@size [] [] [$type:ty]) => (<$type as $crate::value::Size>::SIZE);
+    (@size [$($condition:tt)?] [$($closure:tt)*] [$type:ty]) => (None);
+    (@size $name:ident, $this:ident . $field:ident [] [] [$type:ty]) => (
+        $crate::value::Size::size(&$this.$field)
     );
+    (@size $name:ident, $this:ident . $field:ident [$condition:tt] [] [$type:ty]) => (
+        match &$this.$field {
+            Some(value) => $crate::value::Size::size(value),
+            _ => 0,
+        }
+    );
+    (@size $name:ident, $this:ident . $field:ident [$condition:tt]
+     [$($closure:tt)+] [$type:ty]) => (
+        match &$this.$field {
+            Some(_) => table!(@size $name, $this.$field [] [$($closure)+] [$type]),
+            _ => 0,
+        }
+    );
+    (@size $name:ident, $this:ident . $field:ident [] [$($closure:tt)+] [$type:ty]) => ({
+        compile_error!(concat!(
+            "@size is not supported for ",
+            stringify!($name),
+            " as the field ",
+            stringify!($field),
+            " is written by a closure whose errors cannot be reported by the size",
+        ));
+        0
+    });
+
+    (@fixed pub $name:ident { $($field:ident ($($type:tt)+) [],)* }) => (
+        impl $crate::value::FixedSize for $name {}
+
+        const _: usize = <$name as $crate::value::FixedSize>::SIZE;
+    );
+    (@fixed pub $name:ident { $field:ident ($($type:tt)+) [], $($rest:tt)* }) => (
+        table!(@fixed pub $name { $($rest)* });
+    );
+    (@fixed pub $name:ident { $field:ident ($($type:tt)+) [$($source:tt)+], $($rest:tt)* }) => (
+        compile_error!(concat!(
+            "@fixed is not supported for ",
+            stringify!($name),
+            " as the field ",
+            stringify!($field),
+            " is counted, conditional, or computed by a closure",
+        ));
+    );
+
+    (@view [view ($view:ident) $($flag:tt)*] $name:ident $fields:tt) => (
+        table!(@view ref $name $view [] [0] $fields);
+        table!(@view [$($flag)*] $name $fields);
+    );
+    (@view [view_mut ($view:ident) $($flag:tt)*] $name:ident $fields:tt) => (
+        table!(@view mut $name $view [] [0] $fields);
+        table!(@view [$($flag)*] $name $fields);
+    );
+    (@view [$other:tt $($flag:tt)*] $name:ident $fields:tt) => (
+        table!(@view [$($flag)*] $name $fields);
+    );
+    (@view [] $name:ident $fields:tt) => ();
+    (@view value []) => (None);
+    (@view value [$value:block]) => (Some($value));
+    (@view $kind:tt $name:ident $view:ident [$($done:tt)*] [$($offset:tt)*] {
+        $field:ident ($($type:tt)+) [$($value:block)?] [], $($rest:tt)*
+    }) => (
+        table! {
+            @view $kind $name $view
+            [$($done)* $field ($($type)+) [$($value)?] [$($offset)*],]
+            [$($offset)* + <$($type)+ as $crate::value::FixedSize>::SIZE]
+            { $($rest)* }
+        }
+    );
+    (@view $kind:tt $name:ident $view:ident $done:tt $offset:tt {
+        $field:ident ($($type:tt)+) [$($value:block)?] [$($source:tt)+], $($rest:tt)*
+    }) => (
+        table!(@view $kind $name $view $done $offset {});
+    );
+    (@view ref $name:ident $view:ident [
+        $($field:ident ($($type:tt)+) [$($value:block)?] [$($offset:tt)*],)*
+     ] [$($size:tt)*] {}) => (
+        #[doc = concat!("A view of the leading fields of `", stringify!($name), "`.")]
+        #[derive(Clone, Copy, Debug)]
+        pub struct $view<'l> {
+            bytes: &'l [u8],
+        }
+
+        impl<'l> $view<'l> {
+            /// The size in bytes of the fields in the view.
+            pub const SIZE: usize = $($size)*;
+
+            /// Create an instance given the bytes.
+            pub fn new(bytes: &'l [u8]) -> $crate::Result<Self> {
+                if bytes.len() < Self::SIZE {
+                    $crate::raise!(
+                        concat!(
+                            "found a malformed view ",
+                            stringify!($view),
+                            " with {} bytes fewer than {}",
+                        ),
+                        bytes.len(),
+                        Self::SIZE,
+                    );
+                }
+                Ok(Self { bytes })
+            }
+
+            /// Return the underlying bytes.
+            #[inline]
+            pub fn as_bytes(&self) -> &'l [u8] {
+                self.bytes
+            }
+
+            $(
+                #[doc = concat!("Read the field `", stringify!($field), "`.")]
+                #[inline]
+                pub fn $field(&self) -> $crate::Result<$($type)+> {
+                    $crate::view::read(self.bytes, $($offset)*)
+                }
+            )*
+        }
+    );
+    (@view mut $name:ident $view:ident [
+        $($field:ident ($($type:tt)+) [$($value:block)?] [$($offset:tt)*],)*
+     ] [$($size:tt)*] {}) => (
+        #[doc = concat!("A mutable view of the leading fields of `", stringify!($name), "`.")]
+        #[derive(Debug)]
+        pub struct $view<'l> {
+            bytes: &'l mut [u8],
+        }
+
+        impl<'l> $view<'l> {
+            /// The size in bytes of the fields in the view.
+            pub const SIZE: usize = $($size)*;
+
+            /// Create an instance given the bytes.
+            pub fn new(bytes: &'l mut [u8]) -> $crate::Result<Self> {
+                if bytes.len() < Self::SIZE {
+                    $crate::raise!(
+                        concat!(
+                            "found a malformed view ",
+                            stringify!($view),
+                            " with {} bytes fewer than {}",
+                        ),
+                        bytes.len(),
+                        Self::SIZE,
+                    );
+                }
+                Ok(Self { bytes })
+            }
+
+            /// Return the underlying bytes.
+            #[inline]
+            pub fn as_bytes(&self) -> &[u8] {
+                self.bytes
+            }
+
+            $(
+                #[doc = concat!("Access the field `", stringify!($field), "` in place.")]
+                #[inline]
+                pub fn $field(&mut self) -> $crate::view::FieldMut<'_, $($type)+> {
+                    $crate::view::FieldMut::new(self.bytes, $($offset)*, table!(@view value [$($value)?]))
+                }
+            )*
+        }
+    );
+
+    (@visit pub $name:ident { $($field:ident ($($type:tt)+) [$($condition:tt)?],)* }) => (
+        impl $crate::visit::Visit for $name {
+            fn visit(&self, visitor: &mut dyn $crate::visit::Visitor) {
+                visitor.table(
+                    stringify!($name),
+                    &[
+                        $(
+                            $crate::visit::Field {
+                                name: stringify!($field),
+                                kind: table!(@kind [$($type)+] [$($condition)?]),
+                                value: &self.$field,
+                            },
+                        )*
+                    ],
+                );
+            }
+        }
+    );
+
+    (@flag fixed [fixed $($flag:tt)*] { $($yes:tt)* } { $($no:tt)* }) => ($($yes)*);
+    (@flag position [position $($flag:tt)*] { $($yes:tt)* } { $($no:tt)* }) => ($($yes)*);
+    (@flag serde [serde $($flag:tt)*] { $($yes:tt)* } { $($no:tt)* }) => ($($yes)*);
+    (@flag size [size $($flag:tt)*] { $($yes:tt)* } { $($no:tt)* }) => ($($yes)*);
+    (@flag ttx [ttx $($flag:tt)*] { $($yes:tt)* } { $($no:tt)* }) => ($($yes)*);
+    (@flag visit [visit $($flag:tt)*] { $($yes:tt)* } { $($no:tt)* }) => ($($yes)*);
+    (@flag write [write $($flag:tt)*] { $($yes:tt)* } { $($no:tt)* }) => ($($yes)*);
+    (@flag $name:ident [$other:tt $($flag:tt)*] $yes:tt $no:tt) => (
+        table!(@flag $name [$($flag)*] $yes $no);
+    );
+    (@flag $name:ident [] { $($yes:tt)* } { $($no:tt)* }) => ($($no)*);
+
+    (@table [$($flag:tt)*] @fixed $($rest:tt)*) => (
+        table!(@table [$($flag)* fixed size] $($rest)*);
+    );
+    (@table [$($flag:tt)*] @position $($rest:tt)*) => (
+        table!(@table [$($flag)* position] $($rest)*);
+    );
+    (@table [$($flag:tt)*] @serde $($rest:tt)*) => (
+        table!(@table [$($flag)* serde] $($rest)*);
+    );
+    (@table [$($flag:tt)*] @size $($rest:tt)*) => (
+        table!(@table [$($flag)* size] $($rest)*);
+    );
+    (@table [$($flag:tt)*] @ttx $($rest:tt)*) => (
+        table!(@table [$($flag)* ttx] $($rest)*);
+    );
+    (@table [$($flag:tt)*] @view($view:ident) $($rest:tt)*) => (
+        table!(@table [$($flag)* view($view)] $($rest)*);
+    );
+    (@table [$($flag:tt)*] @view_mut($view:ident) $($rest:tt)*) => (
+        table!(@table [$($flag)* view_mut($view)] $($rest)*);
+    );
+    (@table [$($flag:tt)*] @visit $($rest:tt)*) => (
+        table!(@table [$($flag)* visit] $($rest)*);
+    );
+    (@table [$($flag:tt)*] @write $($rest:tt)*) => (
+        table!(@table [$($flag)* write] $($rest)*);
+    );
+    (@table [$($flag:tt)*] @$other:tt $($rest:tt)*) => (
+        compile_error!(concat!("found an unknown flag @", stringify!($other)));
+    );
+    (@table [$($flag:tt)*] $(#[$attribute:meta])* pub $name:ident {
+        $(
+            $field:ident ($($type:tt)+)
+            $(= $value:block)?
+            $([count = $count:ident])?
+            $(if |$this:tt| $condition:block)?
+            $(|$($argument:tt),+| $body:block)?
+            $(=> |$($write_argument:tt),+| $write_body:block)?,
+        )*
+    }) => (
+        table! {
+            @define
+            $(#[$attribute])* pub $name {
+                $($field ($($type)+) [$({ |$this| $condition })?],)*
+            }
+        }
+        table! {
+            @schema pub $name {
+                $(
+                    $field ($($type)+)
+                    [$($value)?] [$($count)?] [$({ |$this| $condition })?] [$($body)?],
+                )*
+            }
+        }
+        table! {
+            @flag position [$($flag)*] {
+                table! {
+                    @read @position
+                    pub $name {
+                        $(
+                            $field ($($type)+)
+                            [$($value)?] [$($count)?] [$({ |$this| $condition })?]
+                            $(|$($argument),+| $body)?,
+                        )*
+                    }
+                }
+            } {
+                table! {
+                    @read
+                    pub $name {
+                        $(
+                            $field ($($type)+)
+                            [$($value)?] [$($count)?] [$({ |$this| $condition })?]
+                            $(|$($argument),+| $body)?,
+                        )*
+                    }
+                }
+            }
+        }
+        table! {
+            @flag write [$($flag)*] {
+                table! {
+                    @write
+                    pub $name {
+                        $(
+                            $field ($($type)+) [$($count)?] [$({ |$this| $condition })?]
+                            [$(|$($write_argument),+| $write_body)?],
+                        )*
+                    }
+                }
+            } {}
+        }
+        table! {
+            @flag visit [$($flag)*] {
+                table! {
+                    @visit
+                    pub $name { $($field ($($type)+) [$({ |$this| $condition })?],)* }
+                }
+            } {}
+        }
+        table! {
+            @flag serde [$($flag)*] {
+                $crate::implement_serde! {
+                    @table $name {
+                        $($field (table!(@type [$($type)+] [$({ |$this| $condition })?])),)*
+                    }
+                }
+            } {}
+        }
+        table! {
+            @flag ttx [$($flag)*] {
+                $crate::implement_ttx! {
+                    @table $name {
+                        $($field (table!(@type [$($type)+] [$({ |$this| $condition })?])),)*
+                    }
+                }
+            } {}
+        }
+        table! {
+            @flag size [$($flag)*] {
+                table! {
+                    @size
+                    pub $name {
+                        $(
+                            $field ($($type)+) [$({ |$this| $condition })?]
+                            [$(|$($write_argument),+| $write_body)?],
+                        )*
+                    }
+                }
+            } {}
+        }
+        table! {
+            @view [$($flag)*] $name {
+                $(
+                    $field ($($type)+) [$($value)?]
+                    [$($count)? $({ |$this| $condition })? $($body)?],
+                )*
+            }
+        }
+        table! {
+            @flag fixed [$($flag)*] {
+                table! {
+                    @fixed
+                    pub $name {
+                        $(
+                            $field ($($type)+)
+                            [$($count)? $({ |$this| $condition })? $($body)?],
+                        )*
+                    }
+                }
+            } {}
+        }
+    );
+    ($($token:tt)*) => (table! { @table [] $($token)* });
 }
 
 #[cfg(test)]
 mod tests {
+    bitfield! {
+        pub Bitfield(u16) {
+            0..4 => inner_bits (u8) [set_inner_bits],
+            4..6 => entry_size (u8) [set_entry_size],
+            6..16 => rest (u16) [set_rest],
+        }
+    }
+
+    bitfield! {
+        pub SignedBitfield(i16) {
+            0..4 => delta (i8) [set_delta],
+            4..12 => index (u8) [set_index],
+            12..16 => tail (u8),
+        }
+    }
+
+    choices! {
+        pub Choices(u16) {
+            1 => One,
+            2 => Two,
+            _ => Other,
+        }
+    }
+
+    choices! {
+        pub ChoicesWithRanges(u16) {
+            0 => Zero,
+            _ if 0x100..=0xFFF => Reserved,
+            _ => Other,
+        }
+    }
+
+    choices! {
+        pub ChoicesWithoutOther(u8) {
+            1 => One,
+            2 => Two,
+        }
+    }
+
+    choices! {
+        pub ChoicesWithStrings(u8) {
+            1 => One("one"),
+            2 => Two("two"),
+        }
+    }
+
+    flags! {
+        pub Flags(u16) {
+            0b01 => first [FIRST, set_first],
+            0b10 => second [SECOND, set_second],
+        }
+    }
+
+    flags! {
+        pub FlagsWithReserved(u8) {
+            0b01 => first,
+            _ => reserved(0xf0),
+        }
+    }
+
+    flags! {
+        pub SignedFlags(i8) {
+            0x01 => first,
+        }
+    }
+
+    table! {
+        @serde
+        @size
+        @write
+        pub Format1 {
+            format (u16) = { 1 },
+            value (u8),
+        }
+    }
+
+    table! {
+        @serde
+        @write
+        pub Format2 {
+            format (u16) = { 2 },
+            values ([u8; 2]),
+        }
+    }
+
+    formats! {
+        @serde
+        @write
+        pub Formats(u16) {
+            1 => Format1(Format1),
+            2 => Format2(Format2),
+        }
+    }
+
+    formats! {
+        @size
+        @write
+        pub FormatsWithOther(u16) {
+            1 => Format1(Format1),
+            _ => Other(Vec<u8>),
+        }
+    }
+
     table! {
         pub Read {
             major_version (u16) = { 1 },
@@ -640,4 +2373,541 @@ mod tests {
             },
         }
     }
+
+    table! {
+        @visit
+        pub ReadWithVisit {
+            major_version (u16) = { 1 },
+            minor_version (u16),
+
+            records (Vec<u16>) |_, tape| {
+                tape.take_given(0)
+            },
+
+            data (Vec<u8>) |_, tape| {
+                tape.take_given(0)
+            },
+        }
+    }
+
+    table! {
+        @write
+        @size
+        pub ReadAndWriteWithSize {
+            major_version (u16) = { 1 },
+            minor_version (u16),
+
+            records (Vec<u16>) |_, tape| {
+                tape.take_given(0)
+            },
+
+            data (Vec<u8>) |_, tape| {
+                tape.take_given(0)
+            },
+        }
+    }
+
+    table! {
+        @write
+        pub ReadAndWriteWithClosures {
+            count (u16) => |this, tape| {
+                tape.give(&(this.records.len() as u16))
+            },
+
+            records (Vec<u16>) |this, tape| {
+                tape.take_given(this.count as usize)
+            },
+        }
+    }
+
+    table! {
+        @write
+        @size
+        pub ReadAndWriteWithCounts {
+            record_count (u8),
+            records (Vec<u16>) [count = record_count],
+        }
+    }
+
+    table! {
+        @write
+        @size
+        pub ReadAndWriteWithConditions {
+            version (u16),
+            value (u16) if |this| { this.version >= 2 },
+            values ([u8; 2]) if |this| { (this.version, this.value) >= (3, Some(1)) },
+        }
+    }
+
+    table! {
+        @view(ReadWithViewRef)
+        @view_mut(ReadWithViewMut)
+        pub ReadWithView {
+            version (u16) = { 1 },
+            flags (Flags),
+            fraction (crate::q16),
+            count (u16),
+            values (Vec<u16>) [count = count],
+            tail (u32),
+        }
+    }
+
+    table! {
+        @serde
+        @visit
+        @write
+        @size
+        pub Fixed {
+            major_version (u16) = { 1 },
+            minor_version (u16),
+            choices (Choices),
+            choices_without_other (ChoicesWithoutOther),
+            flags (Flags),
+        }
+    }
+
+    #[test]
+    fn bitfield() {
+        use crate::tape::{Read, Write};
+
+        let mut value: Bitfield = std::io::Cursor::new(&[0xff, 0xe3]).take().unwrap();
+        assert_eq!(value.inner_bits(), 3);
+        assert_eq!(value.entry_size(), 2);
+        assert_eq!(value.rest(), 0x3ff);
+        value.set_inner_bits(0xf).unwrap();
+        value.set_entry_size(1).unwrap();
+        value.set_rest(1).unwrap();
+        assert!(value.set_entry_size(4).is_err());
+        assert!(value.set_rest(0x400).is_err());
+        assert_eq!(
+            format!("{value:?}"),
+            "Bitfield { inner_bits: 15, entry_size: 1, rest: 1 }",
+        );
+        let mut tape = std::io::Cursor::new(vec![]);
+        tape.give(&value).unwrap();
+        assert_eq!(tape.into_inner(), [0x00, 0x5f]);
+
+        let mut value = SignedBitfield(-1);
+        assert_eq!(value.delta(), -1);
+        assert_eq!(value.index(), 0xff);
+        assert_eq!(value.tail(), 0xf);
+        value.set_delta(-8).unwrap();
+        assert_eq!(value.delta(), -8);
+        value.set_delta(7).unwrap();
+        assert_eq!(value.delta(), 7);
+        assert!(value.set_delta(8).is_err());
+        assert!(value.set_delta(-9).is_err());
+        value.set_index(0x12).unwrap();
+        assert_eq!(value.0, -0x0ed9);
+        assert_eq!(value.tail(), 0xf);
+        let value = SignedBitfield(0x7123);
+        assert_eq!((value.delta(), value.index(), value.tail()), (3, 0x12, 7));
+    }
+
+    #[test]
+    fn choices() {
+        use crate::tape::Read;
+
+        assert_eq!(Choices::from(1), Choices::One);
+        assert_eq!(
+            ChoicesWithRanges::from(0x100),
+            ChoicesWithRanges::Reserved(0x100)
+        );
+        assert_eq!(
+            ChoicesWithRanges::from(0x1000),
+            ChoicesWithRanges::Other(0x1000)
+        );
+        assert_eq!(u16::from(ChoicesWithRanges::Reserved(0x200)), 0x200);
+
+        let mut tape = std::io::Cursor::new(vec![0x02, 0x00]);
+        let value: ChoicesWithRanges = tape.take().unwrap();
+        assert_eq!(value, ChoicesWithRanges::Reserved(0x200));
+
+        assert_eq!(Choices::ALL, &[Choices::One, Choices::Two]);
+        assert_eq!(ChoicesWithRanges::ALL, &[ChoicesWithRanges::Zero]);
+        assert_eq!(ChoicesWithoutOther::ALL.len(), 2);
+
+        assert_eq!(Choices::Other(42).name(), "Other");
+        assert_eq!(ChoicesWithRanges::Reserved(0x100).to_string(), "Reserved");
+        assert_eq!(ChoicesWithStrings::Two.to_string(), "Two");
+
+        assert_eq!("Two".parse::<Choices>().unwrap(), Choices::Two);
+        assert_eq!("42".parse::<Choices>().unwrap(), Choices::Other(42));
+        assert_eq!(
+            "256".parse::<ChoicesWithRanges>().unwrap(),
+            ChoicesWithRanges::Reserved(0x100),
+        );
+        assert_eq!(
+            "One".parse::<ChoicesWithoutOther>().unwrap(),
+            ChoicesWithoutOther::One
+        );
+        assert!("Three".parse::<Choices>().is_err());
+        assert!("Three".parse::<ChoicesWithoutOther>().is_err());
+
+        assert_eq!(
+            ChoicesWithStrings::try_from("two").unwrap(),
+            ChoicesWithStrings::Two
+        );
+        assert!(ChoicesWithStrings::try_from("Two").is_err());
+    }
+
+    #[test]
+    fn flags() {
+        let mut value = Flags::FIRST | Flags(0b100);
+        assert!(value.first());
+        assert!(!value.second());
+        assert!(value.contains(Flags::FIRST));
+        assert_eq!(format!("{value:?}"), "Flags(first | 0x4)");
+        value.set_first(false);
+        value.set_second(true);
+        assert!(value.contains(Flags::SECOND));
+        assert_eq!(format!("{value:?}"), "Flags(second | 0x4)");
+        assert_eq!(!value, Flags::FIRST);
+        assert_eq!(value & Flags::all(), Flags(0b10));
+        value ^= Flags::all();
+        assert_eq!(value.names().collect::<Vec<_>>(), ["first"]);
+        assert_eq!(format!("{:?}", Flags::empty()), "Flags(0x0)");
+        assert!(Flags::empty().is_empty());
+    }
+
+    #[test]
+    fn flags_with_reserved() {
+        use crate::tape::{Read, Write};
+        use crate::Policy;
+
+        let mut tape = std::io::Cursor::new(vec![0x13]);
+        assert!(tape.take::<FlagsWithReserved>().is_err());
+        tape.set_position(0);
+        let value = tape.take_given::<FlagsWithReserved>(Policy::Reject);
+        assert!(value.is_err());
+        tape.set_position(0);
+        let value = tape.take_given::<FlagsWithReserved>(Policy::Clear).unwrap();
+        assert_eq!(value, FlagsWithReserved(0x03));
+        tape.set_position(0);
+        let value = tape.take_given::<FlagsWithReserved>(Policy::Preserve);
+        let value = value.unwrap();
+        assert_eq!(value, FlagsWithReserved(0x13));
+        assert!(value.is_invalid());
+
+        let mut tape = std::io::Cursor::new(vec![]);
+        assert!(tape.give_given(&value, Policy::Reject).is_err());
+        tape.give_given(&value, Policy::Clear).unwrap();
+        tape.give_given(&value, Policy::Preserve).unwrap();
+        tape.give(&value).unwrap();
+        assert_eq!(tape.into_inner(), [0x03, 0x13, 0x13]);
+    }
+
+    #[test]
+    fn formats() {
+        use crate::tape::{Read, Write};
+
+        let data = vec![0, 2, 3, 4];
+        let value: Formats = std::io::Cursor::new(&data).take().unwrap();
+        assert!(matches!(&value, Formats::Format2(table) if table.values == [3, 4]));
+        let mut tape = std::io::Cursor::new(vec![]);
+        tape.give(&value).unwrap();
+        assert_eq!(tape.into_inner(), data);
+        let value: Formats = std::io::Cursor::new(&[0, 1, 5]).take().unwrap();
+        assert!(matches!(value, Formats::Format1(Format1 { value: 5, .. })));
+
+        let mut tape = std::io::Cursor::new(&[0, 3, 5]);
+        let error = tape.take::<Formats>().unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::Other);
+        let error = error.into_inner().unwrap();
+        assert_eq!(
+            error.downcast_ref::<crate::UnknownFormat>(),
+            Some(&crate::UnknownFormat {
+                name: "Formats",
+                format: 3,
+            }),
+        );
+
+        let data = vec![0, 3, 5];
+        let value: FormatsWithOther = std::io::Cursor::new(&data).take().unwrap();
+        assert!(matches!(&value, FormatsWithOther::Other(value) if value == &data));
+        assert_eq!(crate::value::Size::size(&value), 3);
+        let mut tape = std::io::Cursor::new(vec![]);
+        tape.give(&value).unwrap();
+        assert_eq!(tape.into_inner(), data);
+        let value: FormatsWithOther = std::io::Cursor::new(&[0, 1, 5]).take().unwrap();
+        assert!(matches!(value, FormatsWithOther::Format1(_)));
+    }
+
+    #[test]
+    fn read_with_position() {
+        use crate::tape::{Read, Write};
+
+        let data = vec![0, 1, 0, 2];
+        let table: ReadWithPosition = std::io::Cursor::new(&data).take().unwrap();
+        assert_eq!(table.minor_version, 2);
+        let table: ReadWithPositionAndWrite = std::io::Cursor::new(&data).take().unwrap();
+        assert_eq!(table.minor_version, 2);
+        let mut tape = std::io::Cursor::new(vec![]);
+        tape.give(&table).unwrap();
+        assert_eq!(tape.into_inner(), data);
+    }
+
+    #[test]
+    fn size() {
+        use crate::value::Size;
+
+        assert_eq!(<Fixed as Size>::SIZE, Some(9));
+        assert_eq!(<ReadAndWriteWithSize as Size>::SIZE, None);
+        let table = ReadAndWriteWithSize {
+            records: vec![1, 2],
+            data: vec![3, 4, 5],
+            ..Default::default()
+        };
+        assert_eq!(table.size(), 11);
+    }
+
+    #[test]
+    fn write_with_closures() {
+        use crate::tape::{Read, Write};
+
+        let table = ReadAndWriteWithClosures {
+            records: vec![1, 2],
+            ..Default::default()
+        };
+        let mut tape = std::io::Cursor::new(vec![]);
+        tape.give(&table).unwrap();
+        assert_eq!(tape.get_ref(), &[0, 2, 0, 1, 0, 2]);
+        tape.set_position(0);
+        let table: ReadAndWriteWithClosures = tape.take().unwrap();
+        assert_eq!(table.count, 2);
+        assert_eq!(table.records, [1, 2]);
+    }
+
+    #[test]
+    fn write_with_counts() {
+        use crate::tape::{Read, Write};
+
+        let mut table = ReadAndWriteWithCounts {
+            records: vec![1, 2],
+            ..Default::default()
+        };
+        let mut tape = std::io::Cursor::new(vec![]);
+        tape.give(&table).unwrap();
+        assert_eq!(tape.get_ref(), &[2, 0, 1, 0, 2]);
+        tape.set_position(0);
+        let other: ReadAndWriteWithCounts = tape.take().unwrap();
+        assert_eq!(other.record_count, 2);
+        assert_eq!(other.records, [1, 2]);
+        table.records = vec![0; 256];
+        assert!(std::io::Cursor::new(vec![]).give(&table).is_err());
+    }
+
+    #[test]
+    fn write_with_conditions() {
+        use crate::tape::{Read, Write};
+        use crate::value::Size;
+
+        let table: ReadAndWriteWithConditions = std::io::Cursor::new(&[0, 1, 0, 2]).take().unwrap();
+        assert_eq!(table.value, None);
+        assert_eq!(table.values, None);
+        assert_eq!(table.size(), 2);
+        let data = vec![0, 3, 0, 1, 4, 5];
+        let mut table: ReadAndWriteWithConditions = std::io::Cursor::new(&data).take().unwrap();
+        assert_eq!(table.value, Some(1));
+        assert_eq!(table.values, Some([4, 5]));
+        assert_eq!(table.size(), 6);
+        let mut tape = std::io::Cursor::new(vec![]);
+        tape.give(&table).unwrap();
+        assert_eq!(tape.into_inner(), data);
+        table.version = 1;
+        assert!(std::io::Cursor::new(vec![]).give(&table).is_err());
+    }
+
+    #[test]
+    fn view() {
+        use crate::tape::Read;
+
+        let data = [0, 1, 0, 2, 0x40, 0, 0, 1, 0, 42, 0, 0, 0, 7];
+        let table: ReadWithView = std::io::Cursor::new(&data).take().unwrap();
+        let view = ReadWithViewRef::new(&data).unwrap();
+        assert_eq!(ReadWithViewRef::SIZE, 8);
+        assert_eq!(view.as_bytes().len(), 14);
+        assert_eq!(view.version().unwrap(), table.version);
+        assert_eq!(view.flags().unwrap(), table.flags);
+        assert_eq!(view.fraction().unwrap(), table.fraction);
+        assert_eq!(view.count().unwrap(), table.count);
+        assert!(ReadWithViewRef::new(&data[..7]).is_err());
+
+        let mut data = data;
+        let mut view = ReadWithViewMut::new(&mut data).unwrap();
+        assert_eq!(ReadWithViewMut::SIZE, 8);
+        view.flags().set(Flags::FIRST | Flags(0b10)).unwrap();
+        view.fraction().set(crate::q16(-1)).unwrap();
+        view.version().set(1).unwrap();
+        assert!(view.version().set(2).is_err());
+        assert_eq!(view.count().get().unwrap(), table.count);
+        view.count().set(1).unwrap();
+        assert_eq!(
+            view.as_bytes(),
+            [0, 1, 0, 3, 0xFF, 0xFF, 0, 1, 0, 42, 0, 0, 0, 7]
+        );
+        assert!(ReadWithViewMut::new(&mut data[..7]).is_err());
+    }
+
+    #[test]
+    fn schema() {
+        use crate::schema::{Field, Schema};
+
+        assert_eq!(Fixed::NAME, "Fixed");
+        assert_eq!(
+            Fixed::FIELDS[0],
+            Field {
+                name: "major_version",
+                kind: "u16",
+                value: Some("1"),
+                computed: false,
+                count: None,
+                condition: None,
+                offset: Some(0),
+                size: Some(2),
+            },
+        );
+        let offsets = Fixed::FIELDS.iter().map(|field| field.offset);
+        assert_eq!(
+            offsets.collect::<Vec<_>>(),
+            [Some(0), Some(2), Some(4), Some(6), Some(7)],
+        );
+        let fields = ReadAndWrite::FIELDS;
+        assert_eq!(fields[2].kind, "Vec<u16>");
+        assert!(fields[2].computed);
+        assert_eq!(fields[2].offset, Some(4));
+        assert_eq!(fields[3].offset, None);
+        let fields = ReadAndWriteWithCounts::FIELDS;
+        assert_eq!(fields[1].count, Some("record_count"));
+        assert_eq!(fields[1].offset, Some(1));
+        assert_eq!(fields[1].size, None);
+        let fields = ReadAndWriteWithConditions::FIELDS;
+        assert_eq!(fields[1].kind, "Option<u16>");
+        assert_eq!(fields[1].condition, Some("this.version >= 2"));
+        assert_eq!(fields[1].offset, Some(2));
+        assert_eq!(fields[2].offset, None);
+        let fields = Read::FIELDS;
+        assert_eq!(fields[0].value, Some("1"));
+        assert_eq!(fields[1].offset, Some(2));
+        assert_eq!(fields[1].size, Some(2));
+        assert_eq!(fields[2].offset, Some(4));
+        assert_eq!(fields[3].offset, None);
+        assert_eq!(
+            crate::schema::unwrap("{ \n this.version >= 2\t }"),
+            "this.version >= 2"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let table = Fixed {
+            major_version: 1,
+            minor_version: 2,
+            choices: Choices::Other(42),
+            choices_without_other: ChoicesWithoutOther::Two,
+            flags: Flags(0b101),
+        };
+        let value = serde_json::to_string(&table).unwrap();
+        assert_eq!(
+            value,
+            r#"{"major_version":1,"minor_version":2,"choices":{"Other":42},"choices_without_other":"Two","flags":["first",4]}"#,
+        );
+        let table: Fixed = serde_json::from_str(&value).unwrap();
+        assert_eq!(serde_json::to_string(&table).unwrap(), value);
+        assert!(serde_json::from_str::<Fixed>(r#"{"major_version":1}"#).is_err());
+        assert!(serde_json::from_str::<Flags>(r#"["third"]"#).is_err());
+        assert!(serde_json::from_str::<Choices>(r#"{"Other":1}"#).is_err());
+
+        let value = serde_json::to_string(&SignedFlags(-0x80 | 0x01)).unwrap();
+        assert_eq!(value, r#"["first",128]"#);
+        let value: SignedFlags = serde_json::from_str(&value).unwrap();
+        assert_eq!(value, SignedFlags(-0x80 | 0x01));
+        let value: SignedFlags = serde_json::from_str(r#"["first",-128]"#).unwrap();
+        assert_eq!(value, SignedFlags(-0x80 | 0x01));
+        assert!(serde_json::from_str::<SignedFlags>("[256]").is_err());
+
+        let value = serde_json::to_string(&ChoicesWithRanges::Reserved(0x100)).unwrap();
+        assert_eq!(value, r#"{"Reserved":256}"#);
+        let value: ChoicesWithRanges = serde_json::from_str(&value).unwrap();
+        assert_eq!(value, ChoicesWithRanges::Reserved(0x100));
+        assert!(serde_json::from_str::<ChoicesWithRanges>(r#"{"Reserved":1}"#).is_err());
+        assert!(serde_json::from_str::<ChoicesWithRanges>(r#""Zero""#).is_ok());
+
+        let value = Formats::Format1(Format1 {
+            format: 1,
+            value: 2,
+        });
+        let value = serde_json::to_string(&value).unwrap();
+        assert_eq!(value, r#"{"Format1":{"format":1,"value":2}}"#);
+        let value: Formats = serde_json::from_str(&value).unwrap();
+        assert!(matches!(value, Formats::Format1(Format1 { value: 2, .. })));
+    }
+
+    #[test]
+    fn visit() {
+        use crate::visit::{Field, Leaf, Visit, Visitor};
+
+        #[derive(Default)]
+        struct Render(String);
+
+        impl Visitor for Render {
+            fn leaf(&mut self, leaf: Leaf<'_>) {
+                match leaf {
+                    Leaf::Signed(value) => self.0.push_str(&value.to_string()),
+                    Leaf::Unsigned(value) => self.0.push_str(&value.to_string()),
+                    Leaf::Choice { variant, .. } => self.0.push_str(variant),
+                    Leaf::Flags { names, .. } => self.0.push_str(&names.join("|")),
+                    _ => unreachable!(),
+                }
+            }
+
+            fn table(&mut self, name: &'static str, fields: &[Field<'_>]) {
+                self.0.push_str(name);
+                self.0.push('{');
+                for field in fields {
+                    self.0.push_str(&format!("{}({}):", field.name, field.kind));
+                    field.value.visit(self);
+                    self.0.push(',');
+                }
+                self.0.push('}');
+            }
+
+            fn sequence(&mut self, values: &mut dyn Iterator<Item = &dyn Visit>) {
+                self.0.push('[');
+                for value in values {
+                    value.visit(self);
+                    self.0.push(',');
+                }
+                self.0.push(']');
+            }
+        }
+
+        let table = Fixed {
+            major_version: 1,
+            minor_version: 2,
+            choices: Choices::Other(42),
+            choices_without_other: ChoicesWithoutOther::Two,
+            flags: Flags(0b11),
+        };
+        let mut render = Render::default();
+        table.visit(&mut render);
+        assert_eq!(
+            render.0,
+            "Fixed{major_version(u16):1,minor_version(u16):2,choices(Choices):Other,\
+             choices_without_other(ChoicesWithoutOther):Two,flags(Flags):first|second,}",
+        );
+
+        let table = ReadWithVisit {
+            records: vec![3, 4],
+            ..Default::default()
+        };
+        let mut render = Render::default();
+        table.visit(&mut render);
+        assert_eq!(
+            render.0,
+            "ReadWithVisit{major_version(u16):0,minor_version(u16):0,records(Vec<u16>):[3,4,],\
+             data(Vec<u8>):[],}",
+        );
+    }
 }
@@ -25,6 +25,73 @@ macro_rules! implement {
                 tape.give(&self.0)
             }
         }
+
+        impl crate::value::Size for $name {
+            const SIZE: Option<usize> = <$kind as crate::value::Size>::SIZE;
+
+            #[inline]
+            fn size(&self) -> usize {
+                crate::value::Size::size(&self.0)
+            }
+        }
+
+        impl crate::value::FixedSize for $name {}
+
+        impl crate::visit::Visit for $name {
+            #[inline]
+            fn visit(&self, visitor: &mut dyn crate::visit::Visitor) {
+                visitor.leaf(crate::visit::Leaf::Fixed {
+                    value: self.0 as i64,
+                    fraction: $fraction,
+                });
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl crate::serde::Serialize for $name {
+            fn serialize<T>(&self, serializer: T) -> std::result::Result<T::Ok, T::Error>
+            where
+                T: crate::serde::Serializer,
+            {
+                const SCALE: f64 = (1 << $fraction) as f64;
+                serializer.serialize_f64((self.0 as f64) / SCALE)
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'l> crate::serde::Deserialize<'l> for $name {
+            fn deserialize<T>(deserializer: T) -> std::result::Result<Self, T::Error>
+            where
+                T: crate::serde::Deserializer<'l>,
+            {
+                const SCALE: f64 = (1 << $fraction) as f64;
+                let value = <f64 as crate::serde::Deserialize>::deserialize(deserializer)?;
+                let scaled = value * SCALE;
+                if scaled.fract() != 0.0
+                    || scaled < <$kind>::MIN as f64
+                    || scaled > <$kind>::MAX as f64
+                {
+                    return Err(crate::serde::de::Error::custom(format!(
+                        concat!("found a number {} unrepresentable as ", stringify!($name)),
+                        value,
+                    )));
+                }
+                Ok($name(scaled as $kind))
+            }
+        }
+
+        #[cfg(feature = "ttx")]
+        impl crate::ttx::Load for $name {
+            fn load(element: &crate::ttx::Element) -> $crate::Result<Self> {
+                match <$kind>::try_from(crate::ttx::load_fixed(element, $fraction)?) {
+                    Ok(value) => Ok($name(value)),
+                    _ => error!(
+                        concat!("found a number unrepresentable as ", stringify!($name), " in {}"),
+                        element.name,
+                    ),
+                }
+            }
+        }
     }
 }
 
@@ -40,11 +107,156 @@ implement! {
     pub q32(i32 | 16)
 }
 
+/// A variable-length integer in format UIntBase128.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct UIntBase128(pub u32);
+
+/// A variable-length integer in format 255UInt16.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct UInt255(pub u16);
+
+impl From<UIntBase128> for u32 {
+    #[inline]
+    fn from(number: UIntBase128) -> Self {
+        number.0
+    }
+}
+
+impl From<UInt255> for u16 {
+    #[inline]
+    fn from(number: UInt255) -> Self {
+        number.0
+    }
+}
+
+impl crate::value::Read for UIntBase128 {
+    fn read<T: crate::tape::Read>(tape: &mut T) -> crate::Result<Self> {
+        let mut value = 0u32;
+        for i in 0..5 {
+            let byte = tape.take::<u8>()?;
+            if i == 0 && byte == 0x80 {
+                raise!("found a malformed field of type UIntBase128 with leading zeros");
+            }
+            if value & 0xFE00_0000 != 0 {
+                raise!("found a malformed field of type UIntBase128 exceeding 32 bits");
+            }
+            value = (value << 7) | (byte & 0x7F) as u32;
+            if byte & 0x80 == 0 {
+                return Ok(UIntBase128(value));
+            }
+        }
+        error!("found a malformed field of type UIntBase128 exceeding five bytes")
+    }
+}
+
+impl crate::value::Write for UIntBase128 {
+    fn write<T: crate::tape::Write>(&self, tape: &mut T) -> crate::Result<()> {
+        let count = crate::value::Size::size(self);
+        let mut buffer = [0u8; 5];
+        for (i, byte) in buffer.iter_mut().take(count).enumerate() {
+            *byte = ((self.0 >> (7 * (count - 1 - i))) & 0x7F) as u8;
+            if i + 1 < count {
+                *byte |= 0x80;
+            }
+        }
+        tape.give_bytes(&buffer[..count])
+    }
+}
+
+impl crate::value::Read for UInt255 {
+    fn read<T: crate::tape::Read>(tape: &mut T) -> crate::Result<Self> {
+        const WORD_CODE: u8 = 253;
+        const ONE_MORE_BYTE_CODE_2: u8 = 254;
+        const ONE_MORE_BYTE_CODE_1: u8 = 255;
+        const LOWEST_U_CODE: u16 = 253;
+        match tape.take::<u8>()? {
+            WORD_CODE => Ok(UInt255(tape.take()?)),
+            ONE_MORE_BYTE_CODE_2 => Ok(UInt255(tape.take::<u8>()? as u16 + 2 * LOWEST_U_CODE)),
+            ONE_MORE_BYTE_CODE_1 => Ok(UInt255(tape.take::<u8>()? as u16 + LOWEST_U_CODE)),
+            value => Ok(UInt255(value as u16)),
+        }
+    }
+}
+
+impl crate::value::Write for UInt255 {
+    fn write<T: crate::tape::Write>(&self, tape: &mut T) -> crate::Result<()> {
+        match self.0 {
+            value @ 0..=252 => tape.give(&(value as u8)),
+            value @ 253..=505 => tape.give_bytes(&[255, (value - 253) as u8]),
+            value @ 506..=761 => tape.give_bytes(&[254, (value - 506) as u8]),
+            value => {
+                tape.give(&253u8)?;
+                tape.give(&value)
+            }
+        }
+    }
+}
+
+impl crate::value::Size for UIntBase128 {
+    fn size(&self) -> usize {
+        let mut count = 1;
+        while count < 5 && self.0 >> (7 * count) > 0 {
+            count += 1;
+        }
+        count
+    }
+}
+
+impl crate::value::Size for UInt255 {
+    #[inline]
+    fn size(&self) -> usize {
+        match self.0 {
+            0..=252 => 1,
+            253..=761 => 2,
+            _ => 3,
+        }
+    }
+}
+
+impl crate::visit::Visit for UIntBase128 {
+    #[inline]
+    fn visit(&self, visitor: &mut dyn crate::visit::Visitor) {
+        visitor.leaf(crate::visit::Leaf::Unsigned(self.0 as u64));
+    }
+}
+
+impl crate::visit::Visit for UInt255 {
+    #[inline]
+    fn visit(&self, visitor: &mut dyn crate::visit::Visitor) {
+        visitor.leaf(crate::visit::Leaf::Unsigned(self.0 as u64));
+    }
+}
+
+implement_serde! { @newtype UIntBase128(u32) }
+implement_serde! { @newtype UInt255(u16) }
+
+#[cfg(feature = "ttx")]
+macro_rules! implement {
+    ($name:ident($kind:ty)) => {
+        impl crate::ttx::Load for $name {
+            #[inline]
+            fn load(element: &crate::ttx::Element) -> crate::Result<Self> {
+                <$kind as crate::ttx::Load>::load(element).map($name)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "ttx")]
+implement!(UIntBase128(u32));
+#[cfg(feature = "ttx")]
+implement!(UInt255(u16));
+
 #[cfg(test)]
 mod tests {
-    use super::q16;
+    use std::io::Cursor;
+
+    use super::{q16, UInt255, UIntBase128};
+    use crate::tape::{Read, Write};
+    use crate::value::Size;
 
     #[test]
+    #[allow(clippy::unnecessary_cast)]
     fn from() {
         let cases: Vec<(i16, f32)> = vec![
             (0x7fff as i16, 1.999939),
@@ -59,4 +271,71 @@ mod tests {
             assert!((input - output).abs() < 1e-4, "{} != {}", input, output);
         }
     }
+
+    #[test]
+    fn uint_base128() {
+        let cases: Vec<(u32, Vec<u8>)> = vec![
+            (0, vec![0x00]),
+            (0x7f, vec![0x7f]),
+            (0x80, vec![0x81, 0x00]),
+            (0x3fff, vec![0xff, 0x7f]),
+            (0x4000, vec![0x81, 0x80, 0x00]),
+            (u32::MAX, vec![0x8f, 0xff, 0xff, 0xff, 0x7f]),
+        ];
+        for (value, bytes) in cases.into_iter() {
+            let mut tape = Cursor::new(vec![]);
+            tape.give(&UIntBase128(value)).unwrap();
+            assert_eq!(tape.get_ref(), &bytes);
+            assert_eq!(UIntBase128(value).size(), bytes.len());
+            let mut tape = Cursor::new(bytes);
+            assert_eq!(tape.take::<UIntBase128>().unwrap(), UIntBase128(value));
+        }
+        let cases: Vec<Vec<u8>> = vec![
+            vec![0x80, 0x01],
+            vec![0x90, 0x80, 0x80, 0x80, 0x00],
+            vec![0x81, 0x80, 0x80, 0x80, 0x80, 0x00],
+        ];
+        for bytes in cases.into_iter() {
+            let mut tape = Cursor::new(bytes);
+            assert!(tape.take::<UIntBase128>().is_err());
+        }
+    }
+
+    #[test]
+    fn uint255() {
+        let cases: Vec<(u16, Vec<u8>)> = vec![
+            (0, vec![0]),
+            (252, vec![252]),
+            (253, vec![255, 0]),
+            (505, vec![255, 252]),
+            (506, vec![254, 0]),
+            (761, vec![254, 255]),
+            (762, vec![253, 0x02, 0xfa]),
+            (u16::MAX, vec![253, 0xff, 0xff]),
+        ];
+        for (value, bytes) in cases.into_iter() {
+            let mut tape = Cursor::new(vec![]);
+            tape.give(&UInt255(value)).unwrap();
+            assert_eq!(tape.get_ref(), &bytes);
+            assert_eq!(UInt255(value).size(), bytes.len());
+            let mut tape = Cursor::new(bytes);
+            assert_eq!(tape.take::<UInt255>().unwrap(), UInt255(value));
+        }
+        let mut tape = Cursor::new(vec![253, 0x00, 0x01]);
+        assert_eq!(tape.take::<UInt255>().unwrap(), UInt255(1));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use super::q32;
+
+        for value in [0x7fff, 0x0001, -0x8000] {
+            let string = serde_json::to_string(&q16(value)).unwrap();
+            assert_eq!(serde_json::from_str::<q16>(&string).unwrap(), q16(value));
+        }
+        assert_eq!(serde_json::to_string(&q32(0x00018000)).unwrap(), "1.5");
+        assert!(serde_json::from_str::<q16>("0.1").is_err());
+        assert!(serde_json::from_str::<q16>("2.0").is_err());
+    }
 }
@@ -0,0 +1,11 @@
+/// A policy for reserved bits.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Policy {
+    /// Raise an error if any reserved bit is set.
+    #[default]
+    Reject,
+    /// Clear the reserved bits.
+    Clear,
+    /// Keep the reserved bits as they are.
+    Preserve,
+}
@@ -0,0 +1,117 @@
+//! Static descriptions of tables.
+
+/// A type with a static description.
+pub trait Schema {
+    /// The name.
+    const NAME: &'static str;
+
+    /// The fields in the order they are stored.
+    const FIELDS: &'static [Field];
+}
+
+/// A field of a table.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Field {
+    /// The name.
+    pub name: &'static str,
+    /// The name of the type.
+    pub kind: &'static str,
+    /// The expression of the expected value if the field is constant.
+    pub value: Option<&'static str>,
+    /// The indicator of the field being computed by a closure when reading.
+    pub computed: bool,
+    /// The name of the field holding the number of elements if the field is a collection.
+    pub count: Option<&'static str>,
+    /// The expression of the condition if the field is present only when it holds.
+    pub condition: Option<&'static str>,
+    /// The offset from the beginning of the table if it is known.
+    pub offset: Option<usize>,
+    /// The size in bytes if it is known and the field is neither computed nor conditional.
+    pub size: Option<usize>,
+}
+
+#[doc(hidden)]
+pub struct Probe<T: ?Sized>(std::marker::PhantomData<T>);
+
+#[doc(hidden)]
+pub trait Fallback {
+    const SIZE: Option<usize> = None;
+}
+
+impl<T: ?Sized> Fallback for Probe<T> {}
+
+impl<T: crate::value::Size + ?Sized> Probe<T> {
+    #[doc(hidden)]
+    pub const SIZE: Option<usize> = T::SIZE;
+}
+
+#[doc(hidden)]
+pub const fn locate<const N: usize>(mut fields: [Field; N]) -> [Field; N] {
+    let mut offset = Some(0);
+    let mut i = 0;
+    while i < N {
+        fields[i].offset = offset;
+        offset = match (offset, fields[i].size) {
+            (Some(offset), Some(size)) => Some(offset + size),
+            _ => None,
+        };
+        i += 1;
+    }
+    fields
+}
+
+#[doc(hidden)]
+pub const fn check(fields: &[Field]) {
+    let mut i = 0;
+    while i < fields.len() {
+        if let Some(count) = fields[i].count {
+            let mut j = 0;
+            while j < i && !equal(fields[j].name, count) {
+                j += 1;
+            }
+            if j == i {
+                panic!("found a count naming a field that does not precede the counted field");
+            }
+        }
+        i += 1;
+    }
+}
+
+const fn equal(one: &str, other: &str) -> bool {
+    let (one, other) = (one.as_bytes(), other.as_bytes());
+    if one.len() != other.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < one.len() {
+        if one[i] != other[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+#[doc(hidden)]
+pub const fn unwrap(value: &'static str) -> &'static str {
+    let mut bytes = match value.as_bytes() {
+        [b'{', bytes @ .., b'}'] => bytes,
+        bytes => bytes,
+    };
+    while let [first, rest @ ..] = bytes {
+        if !first.is_ascii_whitespace() {
+            break;
+        }
+        bytes = rest;
+    }
+    while let [rest @ .., last] = bytes {
+        if !last.is_ascii_whitespace() {
+            break;
+        }
+        bytes = rest;
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(value) => value,
+        _ => value,
+    }
+}
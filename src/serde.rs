@@ -0,0 +1,42 @@
+//! Support for serialization.
+
+pub use ::serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+/// An element of serialized flags.
+pub enum Flag {
+    /// A named flag.
+    Name(String),
+    /// Bits without names, with negative values kept as their two's-complement pattern.
+    Bits(u64),
+}
+
+impl<'l> Deserialize<'l> for Flag {
+    fn deserialize<T: Deserializer<'l>>(deserializer: T) -> Result<Self, T::Error> {
+        struct Visitor;
+
+        impl de::Visitor<'_> for Visitor {
+            type Value = Flag;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a flag name or unnamed bits")
+            }
+
+            #[inline]
+            fn visit_str<T: de::Error>(self, value: &str) -> Result<Flag, T> {
+                Ok(Flag::Name(value.into()))
+            }
+
+            #[inline]
+            fn visit_u64<T: de::Error>(self, value: u64) -> Result<Flag, T> {
+                Ok(Flag::Bits(value))
+            }
+
+            #[inline]
+            fn visit_i64<T: de::Error>(self, value: i64) -> Result<Flag, T> {
+                Ok(Flag::Bits(value as u64))
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
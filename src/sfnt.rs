@@ -0,0 +1,425 @@
+//! The container of tables shared by TrueType and OpenType fonts.
+
+use std::collections::BTreeMap;
+
+use crate::Result;
+
+/// The constant that the checksum of a font is adjusted to.
+pub const CHECKSUM_MAGIC: u32 = 0xB1B0AFBA;
+
+const HEAD: Tag = Tag(*b"head");
+
+/// A table tag.
+#[derive(Clone, Copy, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Tag(pub [u8; 4]);
+
+table! {
+    @write
+    @fixed
+    /// An offset table.
+    pub Header {
+        version (u32),
+        table_count (u16),
+        search_range (u16),
+        entry_selector (u16),
+        range_shift (u16),
+    }
+}
+
+table! {
+    @write
+    @fixed
+    /// A table record.
+    pub Record {
+        tag (Tag),
+        checksum (u32),
+        offset (u32),
+        size (u32),
+    }
+}
+
+/// A table directory.
+#[derive(Clone, Debug, Default)]
+pub struct Directory {
+    /// The version of the font.
+    pub version: u32,
+    /// The records sorted by tag.
+    pub records: BTreeMap<Tag, Record>,
+}
+
+/// A writer of a font given its tables.
+#[derive(Clone, Debug, Default)]
+pub struct Writer {
+    version: u32,
+    tables: BTreeMap<Tag, Vec<u8>>,
+}
+
+impl Tag {
+    /// Return the bytes as a string if they are valid UTF-8.
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        std::str::from_utf8(&self.0).ok()
+    }
+}
+
+impl From<&[u8; 4]> for Tag {
+    #[inline]
+    fn from(value: &[u8; 4]) -> Self {
+        Self(*value)
+    }
+}
+
+impl std::str::FromStr for Tag {
+    type Err = crate::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.as_bytes().try_into() {
+            Ok(value) => Ok(Self(value)),
+            _ => error!("found a malformed tag {:?}", value),
+        }
+    }
+}
+
+impl std::fmt::Debug for Tag {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.as_str() {
+            Some(value) => write!(formatter, "Tag({value:?})"),
+            _ => write!(formatter, "Tag({:?})", self.0),
+        }
+    }
+}
+
+impl std::fmt::Display for Tag {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.as_str() {
+            Some(value) => formatter.write_str(value),
+            _ => write!(formatter, "0x{:08X}", u32::from_be_bytes(self.0)),
+        }
+    }
+}
+
+impl crate::value::Read for Tag {
+    #[inline]
+    fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
+        Ok(Self(tape.take()?))
+    }
+}
+
+impl crate::value::Write for Tag {
+    #[inline]
+    fn write<T: crate::tape::Write>(&self, tape: &mut T) -> Result<()> {
+        tape.give(&self.0)
+    }
+}
+
+impl crate::value::Size for Tag {
+    const SIZE: Option<usize> = Some(4);
+
+    #[inline]
+    fn size(&self) -> usize {
+        4
+    }
+}
+
+impl crate::value::FixedSize for Tag {}
+
+impl crate::visit::Visit for Tag {
+    fn visit(&self, visitor: &mut dyn crate::visit::Visitor) {
+        match self.as_str() {
+            Some(value) => visitor.leaf(crate::visit::Leaf::String(value)),
+            _ => visitor.leaf(crate::visit::Leaf::Unsigned(
+                u32::from_be_bytes(self.0).into(),
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl crate::serde::Serialize for Tag {
+    fn serialize<T>(&self, serializer: T) -> std::result::Result<T::Ok, T::Error>
+    where
+        T: crate::serde::Serializer,
+    {
+        match self.as_str() {
+            Some(value) => serializer.serialize_str(value),
+            _ => Err(crate::serde::ser::Error::custom(format!(
+                "found a tag {self} with non-UTF-8 bytes",
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'l> crate::serde::Deserialize<'l> for Tag {
+    fn deserialize<T>(deserializer: T) -> std::result::Result<Self, T::Error>
+    where
+        T: crate::serde::Deserializer<'l>,
+    {
+        let value = <String as crate::serde::Deserialize>::deserialize(deserializer)?;
+        value.parse().map_err(crate::serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "ttx")]
+impl crate::ttx::Load for Tag {
+    #[inline]
+    fn load(element: &crate::ttx::Element) -> Result<Self> {
+        element.value()?.parse()
+    }
+}
+
+impl Directory {
+    /// Return the bytes of a table given the bytes of the font.
+    pub fn window<'l>(&self, bytes: &'l [u8], tag: Tag) -> Result<Option<&'l [u8]>> {
+        let record = match self.records.get(&tag) {
+            Some(record) => record,
+            _ => return Ok(None),
+        };
+        let start = record.offset as usize;
+        let end = start.checked_add(record.size as usize);
+        match end.and_then(|end| bytes.get(start..end)) {
+            Some(bytes) => Ok(Some(bytes)),
+            _ => error!(
+                "found a table {} exceeding the bounds of the font with {} bytes",
+                tag,
+                bytes.len(),
+            ),
+        }
+    }
+
+    /// Read a table given the tape of the font.
+    pub fn take<T, U>(&self, tape: &mut T, tag: Tag) -> Result<Option<U>>
+    where
+        T: crate::tape::Read,
+        U: crate::value::Read,
+    {
+        match self.records.get(&tag) {
+            Some(record) => {
+                tape.jump(record.offset as u64)?;
+                Ok(Some(tape.take()?))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+impl crate::value::Read for Directory {
+    fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
+        let header = tape.take::<Header>()?;
+        match &header.version.to_be_bytes() {
+            [0x00, 0x01, 0x00, 0x00] | b"OTTO" | b"true" | b"typ1" => {}
+            _ => raise!("found a malformed font version {:#010x}", header.version),
+        }
+        let mut records = BTreeMap::new();
+        for _ in 0..header.table_count {
+            let record = tape.take::<Record>()?;
+            let tag = record.tag;
+            if records.insert(tag, record).is_some() {
+                raise!("found a duplicate table {}", tag);
+            }
+        }
+        Ok(Self {
+            version: header.version,
+            records,
+        })
+    }
+}
+
+impl Writer {
+    /// Create an instance given the version of the font.
+    #[inline]
+    pub fn new(version: u32) -> Self {
+        Self {
+            version,
+            tables: Default::default(),
+        }
+    }
+
+    /// Return the tables.
+    #[inline]
+    pub fn tables(&self) -> &BTreeMap<Tag, Vec<u8>> {
+        &self.tables
+    }
+
+    /// Return the version of the font.
+    #[inline]
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Add a table replacing the one with the same tag if any.
+    pub fn add<T: crate::value::Write + ?Sized>(&mut self, tag: Tag, value: &T) -> Result<()> {
+        let mut bytes = vec![];
+        crate::value::Write::write(value, &mut bytes)?;
+        self.tables.insert(tag, bytes);
+        Ok(())
+    }
+}
+
+impl crate::value::Write for Writer {
+    fn write<T: crate::tape::Write>(&self, tape: &mut T) -> Result<()> {
+        let mut tables = clear(&self.tables)?;
+        let mut offsets = BTreeMap::new();
+        let mut offset = 12 + 16 * tables.len();
+        for (tag, bytes) in tables.iter() {
+            offsets.insert(*tag, offset);
+            offset += pad(bytes.len());
+        }
+        let directory = describe(self.version, &tables, &offsets)?;
+        adjust(&mut tables, &directory);
+        tape.give_bytes(&directory)?;
+        for bytes in tables.values() {
+            give_padded(tape, bytes)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compute the checksum of a table.
+pub fn checksum(bytes: &[u8]) -> u32 {
+    bytes.chunks(4).fold(0u32, |total, chunk| {
+        let mut value = [0; 4];
+        value[..chunk.len()].copy_from_slice(chunk);
+        total.wrapping_add(u32::from_be_bytes(value))
+    })
+}
+
+pub(crate) fn adjust(tables: &mut BTreeMap<Tag, Vec<u8>>, directory: &[u8]) {
+    let total = tables.values().fold(checksum(directory), |total, bytes| {
+        total.wrapping_add(checksum(bytes))
+    });
+    if let Some(bytes) = tables.get_mut(&HEAD) {
+        bytes[8..12].copy_from_slice(&CHECKSUM_MAGIC.wrapping_sub(total).to_be_bytes());
+    }
+}
+
+pub(crate) fn clear(tables: &BTreeMap<Tag, Vec<u8>>) -> Result<BTreeMap<Tag, Vec<u8>>> {
+    let mut tables = tables.clone();
+    if let Some(bytes) = tables.get_mut(&HEAD) {
+        if bytes.len() < 12 {
+            raise!("found a malformed table head with {} bytes", bytes.len());
+        }
+        bytes[8..12].fill(0);
+    }
+    Ok(tables)
+}
+
+pub(crate) fn describe(
+    version: u32,
+    tables: &BTreeMap<Tag, Vec<u8>>,
+    offsets: &BTreeMap<Tag, usize>,
+) -> Result<Vec<u8>> {
+    let count = tables.len();
+    let entry_selector = if count > 0 { count.ilog2() } else { 0 };
+    let search_range = if count > 0 {
+        (1 << entry_selector) * 16
+    } else {
+        0
+    };
+    let header = match (
+        u16::try_from(count),
+        u16::try_from(search_range),
+        u16::try_from(count * 16 - search_range),
+    ) {
+        (Ok(table_count), Ok(search_range), Ok(range_shift)) => Header {
+            version,
+            table_count,
+            search_range,
+            entry_selector: entry_selector as u16,
+            range_shift,
+        },
+        _ => raise!("found {} tables exceeding the limit", count),
+    };
+    let mut records = Vec::with_capacity(count);
+    for (tag, bytes) in tables.iter() {
+        match (u32::try_from(offsets[tag]), u32::try_from(bytes.len())) {
+            (Ok(offset), Ok(size)) => records.push(Record {
+                tag: *tag,
+                checksum: checksum(bytes),
+                offset,
+                size,
+            }),
+            _ => raise!("found a table {} exceeding the limit of the offset", tag),
+        }
+    }
+    let mut directory = vec![];
+    crate::tape::Write::give(&mut directory, &header)?;
+    crate::tape::Write::give(&mut directory, &records[..])?;
+    Ok(directory)
+}
+
+pub(crate) fn give_padded<T: crate::tape::Write>(tape: &mut T, bytes: &[u8]) -> Result<()> {
+    tape.give_bytes(bytes)?;
+    tape.give_bytes(&[0; 3][..(pad(bytes.len()) - bytes.len())])
+}
+
+#[inline]
+pub(crate) fn pad(size: usize) -> usize {
+    (size + 3) & !3
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{checksum, Directory, Tag, Writer, CHECKSUM_MAGIC};
+    use crate::tape::{Read, Write};
+
+    #[test]
+    fn checksums() {
+        assert_eq!(checksum(&[]), 0);
+        assert_eq!(checksum(&[0, 0, 1, 0, 1]), 0x100 + 0x1000000);
+        assert_eq!(checksum(&[0xFF; 8]), 0xFFFFFFFE);
+    }
+
+    #[test]
+    fn write() {
+        let mut head = vec![0u8; 54];
+        head[8..12].copy_from_slice(&[1, 2, 3, 4]);
+        head[12..16].copy_from_slice(&0x5F0F3CF5u32.to_be_bytes());
+
+        let mut writer = Writer::new(0x00010000);
+        writer.add(Tag(*b"name"), &vec![1u8, 2, 3]).unwrap();
+        writer.add(Tag(*b"head"), &head).unwrap();
+        writer.add(Tag(*b"OS/2"), &[0x42u16; 3]).unwrap();
+        let mut tape = std::io::Cursor::new(vec![]);
+        tape.give(&writer).unwrap();
+        let bytes = tape.into_inner();
+
+        assert_eq!(bytes.len() % 4, 0);
+        assert_eq!(checksum(&bytes), CHECKSUM_MAGIC);
+        assert_eq!(&bytes[4..12], &[0, 3, 0, 32, 0, 1, 0, 16]);
+
+        let directory: Directory = std::io::Cursor::new(&bytes).take().unwrap();
+        assert_eq!(directory.version, 0x00010000);
+        let tags = directory
+            .records
+            .keys()
+            .map(Tag::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(tags, ["OS/2", "head", "name"]);
+        for record in directory.records.values() {
+            assert_eq!(record.offset % 4, 0);
+        }
+
+        let window = directory.window(&bytes, Tag(*b"name")).unwrap().unwrap();
+        assert_eq!(window, [1, 2, 3]);
+        let window = directory.window(&bytes, Tag(*b"head")).unwrap().unwrap();
+        assert_eq!(window[12..16], head[12..16]);
+        assert_ne!(window[8..12], head[8..12]);
+        let record = &directory.records[&Tag(*b"head")];
+        let mut head = window.to_vec();
+        head[8..12].fill(0);
+        assert_eq!(record.checksum, checksum(&head));
+        assert!(directory.window(&bytes, Tag(*b"glyf")).unwrap().is_none());
+        assert!(directory.window(&bytes[..40], Tag(*b"name")).is_err());
+
+        let mut tape = std::io::Cursor::new(&bytes);
+        let values: Option<[u16; 3]> = directory.take(&mut tape, Tag(*b"OS/2")).unwrap();
+        assert_eq!(values, Some([0x42; 3]));
+
+        let mut bytes = bytes;
+        bytes[0..4].copy_from_slice(b"wOFF");
+        assert!(std::io::Cursor::new(&bytes).take::<Directory>().is_err());
+        bytes[0..4].copy_from_slice(b"OTTO");
+        assert!(std::io::Cursor::new(&bytes).take::<Directory>().is_ok());
+    }
+}
@@ -34,6 +34,14 @@ pub trait Read: std::io::Read + std::io::Seek + Sized {
         self.stream_position()
     }
 
+    #[doc(hidden)]
+    fn remaining(&mut self) -> Result<u64> {
+        let position = self.position()?;
+        let end = self.seek(std::io::SeekFrom::End(0))?;
+        self.jump(position)?;
+        Ok(end.saturating_sub(position))
+    }
+
     #[doc(hidden)]
     fn stay<F, T>(&mut self, mut body: F) -> Result<T>
     where
@@ -62,6 +70,16 @@ pub trait Write: std::io::Write + Sized {
         crate::value::Write::write(value, self)
     }
 
+    /// Write a value given a parameter.
+    #[inline]
+    fn give_given<'l, T: crate::walue::Write<'l> + ?Sized>(
+        &mut self,
+        value: &T,
+        parameter: T::Parameter,
+    ) -> Result<()> {
+        crate::walue::Write::write(value, self, parameter)
+    }
+
     #[doc(hidden)]
     #[inline]
     fn give_bytes(&mut self, value: &[u8]) -> Result<()> {
@@ -0,0 +1,239 @@
+//! The container of fonts sharing tables.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::sfnt::{Directory, Tag};
+use crate::Result;
+
+table! {
+    @write
+    /// A collection header.
+    pub Header {
+        tag (Tag) = { Tag(*b"ttcf") },
+        major_version (u16),
+        minor_version (u16),
+        font_count (u32),
+        offsets (Vec<u32>) [count = font_count],
+        signature_tag (u32) if |this| { this.major_version >= 2 },
+        signature_size (u32) if |this| { this.major_version >= 2 },
+        signature_offset (u32) if |this| { this.major_version >= 2 },
+    }
+}
+
+/// A collection of fonts.
+#[derive(Clone, Debug, Default)]
+pub struct Collection {
+    /// The header.
+    pub header: Header,
+    /// The directories of the fonts in the order of the header.
+    pub directories: Vec<Directory>,
+}
+
+/// A writer of a collection given its fonts.
+///
+/// Tables that are byte-identical across fonts are written once. When the table `head` is
+/// shared, its checksum adjustment is computed with respect to the first font using it.
+#[derive(Clone, Debug, Default)]
+pub struct Writer {
+    fonts: Vec<crate::sfnt::Writer>,
+    signature: Option<Vec<u8>>,
+}
+
+impl crate::value::Read for Collection {
+    fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
+        let header = tape.take::<Header>()?;
+        let mut directories = Vec::with_capacity(header.offsets.len());
+        for offset in header.offsets.iter() {
+            tape.jump(*offset as u64)?;
+            directories.push(tape.take()?);
+        }
+        Ok(Self {
+            header,
+            directories,
+        })
+    }
+}
+
+impl Writer {
+    /// Add a font.
+    #[inline]
+    pub fn add(&mut self, font: crate::sfnt::Writer) {
+        self.fonts.push(font);
+    }
+
+    /// Set the digital signature, which makes the header of version 2.
+    #[inline]
+    pub fn sign(&mut self, signature: Vec<u8>) {
+        self.signature = Some(signature);
+    }
+}
+
+impl crate::value::Write for Writer {
+    fn write<T: crate::tape::Write>(&self, tape: &mut T) -> Result<()> {
+        use crate::sfnt::{adjust, clear, describe, give_padded, pad};
+
+        let count = self.fonts.len();
+        let mut offset = 12 + 4 * count + if self.signature.is_some() { 12 } else { 0 };
+        let mut offsets = Vec::with_capacity(count);
+        let mut fonts = Vec::with_capacity(count);
+        for font in self.fonts.iter() {
+            let tables = clear(font.tables())?;
+            offsets.push(offset);
+            offset += 12 + 16 * tables.len();
+            fonts.push(tables);
+        }
+
+        let mut unique: Vec<(usize, Vec<u8>)> = vec![];
+        let mut indices: HashMap<&[u8], usize> = HashMap::new();
+        let mut placements = Vec::with_capacity(count);
+        for tables in fonts.iter() {
+            let mut placement = BTreeMap::new();
+            for (tag, bytes) in tables.iter() {
+                let index = *indices.entry(bytes.as_slice()).or_insert_with(|| {
+                    unique.push((offset, bytes.clone()));
+                    offset += pad(bytes.len());
+                    unique.len() - 1
+                });
+                placement.insert(*tag, index);
+            }
+            placements.push(placement);
+        }
+
+        let mut directories = Vec::with_capacity(count);
+        let mut adjusted = vec![false; unique.len()];
+        for ((font, tables), placement) in self.fonts.iter().zip(&fonts).zip(&placements) {
+            let offsets = placement
+                .iter()
+                .map(|(tag, index)| (*tag, unique[*index].0))
+                .collect();
+            let directory = describe(font.version(), tables, &offsets)?;
+            if let Some(&index) = placement.get(&Tag(*b"head")) {
+                if !adjusted[index] {
+                    let mut tables = tables.clone();
+                    adjust(&mut tables, &directory);
+                    unique[index].1 = tables.remove(&Tag(*b"head")).unwrap_or_default();
+                    adjusted[index] = true;
+                }
+            }
+            directories.push(directory);
+        }
+
+        let mut header = Header {
+            tag: Tag(*b"ttcf"),
+            major_version: 1,
+            minor_version: 0,
+            ..Default::default()
+        };
+        for value in offsets {
+            match u32::try_from(value) {
+                Ok(value) => header.offsets.push(value),
+                _ => raise!("found a font exceeding the limit of the offset"),
+            }
+        }
+        if let Some(signature) = self.signature.as_ref() {
+            match (u32::try_from(signature.len()), u32::try_from(offset)) {
+                (Ok(size), Ok(offset)) => {
+                    header.major_version = 2;
+                    header.signature_tag = Some(u32::from_be_bytes(*b"DSIG"));
+                    header.signature_size = Some(size);
+                    header.signature_offset = Some(offset);
+                }
+                _ => raise!("found a signature exceeding the limit of the offset"),
+            }
+        }
+
+        tape.give(&header)?;
+        for directory in directories.iter() {
+            tape.give_bytes(directory)?;
+        }
+        for (_, bytes) in unique.iter() {
+            give_padded(tape, bytes)?;
+        }
+        if let Some(signature) = self.signature.as_ref() {
+            tape.give_bytes(signature)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Collection, Writer};
+    use crate::sfnt::{checksum, Tag, CHECKSUM_MAGIC};
+    use crate::tape::{Read, Write};
+
+    #[test]
+    fn write() {
+        let head = vec![0u8; 54];
+
+        let mut one = crate::sfnt::Writer::new(0x00010000);
+        one.add(Tag(*b"head"), &head).unwrap();
+        one.add(Tag(*b"glyf"), &vec![1u8, 2, 3, 4, 5]).unwrap();
+        one.add(Tag(*b"name"), &vec![6u8]).unwrap();
+        let mut other = crate::sfnt::Writer::new(0x00010000);
+        other.add(Tag(*b"head"), &head).unwrap();
+        other.add(Tag(*b"glyf"), &vec![1u8, 2, 3, 4, 5]).unwrap();
+        other.add(Tag(*b"name"), &vec![7u8]).unwrap();
+
+        let mut writer = Writer::default();
+        writer.add(one);
+        writer.add(other);
+        writer.sign(vec![0, 0, 0, 1, 0, 0, 0, 0]);
+        let mut tape = std::io::Cursor::new(vec![]);
+        tape.give(&writer).unwrap();
+        let bytes = tape.into_inner();
+
+        let collection: Collection = std::io::Cursor::new(&bytes).take().unwrap();
+        assert_eq!(collection.header.major_version, 2);
+        assert_eq!(collection.header.offsets, [32, 92]);
+        assert_eq!(collection.header.signature_size, Some(8));
+        let offset = collection.header.signature_offset.unwrap() as usize;
+        assert_eq!(bytes[offset..], [0, 0, 0, 1, 0, 0, 0, 0]);
+        assert_eq!(collection.directories.len(), 2);
+
+        let [one, other] = &collection.directories[..] else {
+            unreachable!();
+        };
+        let glyf = Tag(*b"glyf");
+        let head = Tag(*b"head");
+        let name = Tag(*b"name");
+        assert_eq!(one.records[&glyf].offset, other.records[&glyf].offset);
+        assert_eq!(one.records[&head].offset, other.records[&head].offset);
+        assert_ne!(one.records[&name].offset, other.records[&name].offset);
+        assert_eq!(one.window(&bytes, name).unwrap().unwrap(), [6]);
+        assert_eq!(other.window(&bytes, name).unwrap().unwrap(), [7]);
+        assert_eq!(
+            other.window(&bytes, glyf).unwrap().unwrap(),
+            [1, 2, 3, 4, 5]
+        );
+
+        let start = collection.header.offsets[0] as usize;
+        let total = one.records.values().fold(
+            checksum(&bytes[start..(start + 12 + 16 * 3)]),
+            |total, record| {
+                let bytes = one.window(&bytes, record.tag).unwrap().unwrap();
+                total.wrapping_add(checksum(bytes))
+            },
+        );
+        assert_eq!(total, CHECKSUM_MAGIC);
+
+        let mut writer = Writer::default();
+        writer.add(crate::sfnt::Writer::new(0x00010000));
+        let mut tape = std::io::Cursor::new(vec![]);
+        tape.give(&writer).unwrap();
+        let collection: Collection = std::io::Cursor::new(tape.into_inner()).take().unwrap();
+        assert_eq!(collection.header.major_version, 1);
+        assert_eq!(collection.header.signature_tag, None);
+        assert!(collection.directories[0].records.is_empty());
+    }
+
+    #[test]
+    fn read_malformed() {
+        let bytes = [
+            b't', b't', b'c', b'f', 0, 1, 0, 0, 0xff, 0xff, 0xff, 0xff, 0, 0, 0, 12,
+        ];
+        assert!(std::io::Cursor::new(&bytes).take::<Collection>().is_err());
+        let bytes = [b't', b't', b'c', b'f', 0, 1, 0, 0, 0, 0, 0, 2, 0, 0, 0, 12];
+        assert!(std::io::Cursor::new(&bytes).take::<Collection>().is_err());
+    }
+}
@@ -0,0 +1,774 @@
+//! Dumping and loading in the TTX format.
+//!
+//! Tables are represented as elements whose children are named after the fields in camel case
+//! the way fontTools names them, so that `units_per_em` becomes `unitsPerEm`. Fields with
+//! values without parts are written as `<name value="..."/>`, sequences are written as repeated
+//! elements with an `index` attribute, and absent values are omitted. Fixed-point numbers are
+//! formatted the way fontTools does, and flags are written as groups of eight bits.
+
+use crate::visit::{Field, Leaf, Visit, Visitor};
+use crate::Result;
+
+/// A type that can be loaded.
+pub trait Load: Sized {
+    /// Load a value from an element.
+    fn load(_: &Element) -> Result<Self>;
+
+    /// Load a value from all the elements with the same name.
+    fn load_all(elements: &[&Element]) -> Result<Self> {
+        match elements {
+            [element] => Self::load(element),
+            _ => error!(
+                "expected one element but found {} for a value of type {}",
+                elements.len(),
+                std::any::type_name::<Self>(),
+            ),
+        }
+    }
+}
+
+/// An element.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Element {
+    /// The name.
+    pub name: String,
+    /// The attributes.
+    pub attributes: Vec<(String, String)>,
+    /// The children.
+    pub children: Vec<Element>,
+}
+
+/// Dump a value into an element with a given name.
+pub fn dump<T: Visit + ?Sized>(value: &T, name: &str) -> Result<Element> {
+    let mut dumper = Dumper::new(name, None);
+    value.visit(&mut dumper);
+    match dumper.elements.len() {
+        1 => Ok(dumper.elements.remove(0)),
+        count => error!("expected one element but found {count} when dumping {name}"),
+    }
+}
+
+/// Load a value from a document.
+pub fn load<T: Load>(text: &str) -> Result<T> {
+    T::load(&Element::parse(text)?)
+}
+
+impl Element {
+    /// Create an element.
+    #[inline]
+    pub fn new<T: Into<String>>(name: T) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Return the value of an attribute.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(other, _)| other == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Return the children with a name.
+    pub fn children_named<'l>(&'l self, name: &'l str) -> impl Iterator<Item = &'l Element> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    /// Return the value attribute.
+    pub fn value(&self) -> Result<&str> {
+        match self.attribute("value") {
+            Some(value) => Ok(value),
+            _ => error!("found no value attribute in element {}", self.name),
+        }
+    }
+
+    /// Parse a document and return its root element.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut parser = Parser { text, position: 0 };
+        parser.skip_prolog()?;
+        let element = parser.element()?;
+        parser.skip_miscellany()?;
+        if parser.position < text.len() {
+            raise!("found trailing content at position {}", parser.position);
+        }
+        Ok(element)
+    }
+
+    fn format(&self, formatter: &mut std::fmt::Formatter, depth: usize) -> std::fmt::Result {
+        write!(formatter, "{:1$}<{2}", "", 2 * depth, self.name)?;
+        for (name, value) in self.attributes.iter() {
+            write!(formatter, " {}=\"{}\"", name, escape(value))?;
+        }
+        if self.children.is_empty() {
+            return writeln!(formatter, "/>");
+        }
+        writeln!(formatter, ">")?;
+        for child in self.children.iter() {
+            child.format(formatter, depth + 1)?;
+        }
+        writeln!(formatter, "{:1$}</{2}>", "", 2 * depth, self.name)
+    }
+}
+
+impl std::fmt::Display for Element {
+    #[inline]
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.format(formatter, 0)
+    }
+}
+
+struct Dumper {
+    name: String,
+    index: Option<usize>,
+    elements: Vec<Element>,
+}
+
+impl Dumper {
+    fn new(name: &str, index: Option<usize>) -> Self {
+        Self {
+            name: name.into(),
+            index,
+            elements: vec![],
+        }
+    }
+
+    fn element(&self) -> Element {
+        let mut element = Element::new(self.name.clone());
+        if let Some(index) = self.index {
+            element.attributes.push(("index".into(), index.to_string()));
+        }
+        element
+    }
+}
+
+impl Visitor for Dumper {
+    fn leaf(&mut self, leaf: Leaf<'_>) {
+        let value = match leaf {
+            Leaf::None => return,
+            Leaf::Signed(value) => value.to_string(),
+            Leaf::Unsigned(value) => value.to_string(),
+            Leaf::Fixed { value, fraction } => format_fixed(value, fraction),
+            Leaf::String(value) => value.into(),
+            Leaf::Choice { value, .. } => return value.visit(self),
+            Leaf::Flags { value, size, .. } => format_flags(value, size),
+        };
+        let mut element = self.element();
+        element.attributes.push(("value".into(), value));
+        self.elements.push(element);
+    }
+
+    fn table(&mut self, _: &'static str, fields: &[Field<'_>]) {
+        let mut element = self.element();
+        for field in fields {
+            let mut dumper = Dumper::new(&rename(field.name), None);
+            field.value.visit(&mut dumper);
+            element.children.extend(dumper.elements);
+        }
+        self.elements.push(element);
+    }
+
+    fn sequence(&mut self, values: &mut dyn Iterator<Item = &dyn Visit>) {
+        if self.index.is_some() {
+            let mut element = self.element();
+            for (index, value) in values.enumerate() {
+                let mut dumper = Dumper::new("item", Some(index));
+                value.visit(&mut dumper);
+                element.children.extend(dumper.elements);
+            }
+            self.elements.push(element);
+        } else {
+            for (index, value) in values.enumerate() {
+                let mut dumper = Dumper::new(&self.name, Some(index));
+                value.visit(&mut dumper);
+                self.elements.extend(dumper.elements);
+            }
+        }
+    }
+}
+
+struct Parser<'l> {
+    text: &'l str,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn element(&mut self) -> Result<Element> {
+        self.expect("<")?;
+        let mut element = Element::new(self.name()?);
+        loop {
+            self.skip_whitespace();
+            if self.consume("/>") {
+                return Ok(element);
+            }
+            if self.consume(">") {
+                break;
+            }
+            let name = self.name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let value = self.quoted()?;
+            element.attributes.push((name, value));
+        }
+        loop {
+            self.skip_miscellany()?;
+            if self.consume("</") {
+                let name = self.name()?;
+                if name != element.name {
+                    raise!("found a closing tag {} for element {}", name, element.name);
+                }
+                self.skip_whitespace();
+                self.expect(">")?;
+                return Ok(element);
+            }
+            if self.rest().starts_with('<') {
+                element.children.push(self.element()?);
+                continue;
+            }
+            match self.rest().find('<') {
+                Some(offset) => self.position += offset,
+                _ => raise!("found an unclosed element {}", element.name),
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String> {
+        let size = self
+            .rest()
+            .find(|character: char| {
+                !(character.is_alphanumeric() || matches!(character, '_' | '-' | '.' | ':'))
+            })
+            .unwrap_or(self.rest().len());
+        if size == 0 {
+            raise!("expected a name at position {}", self.position);
+        }
+        let name = self.rest()[..size].to_string();
+        self.position += size;
+        Ok(name)
+    }
+
+    fn quoted(&mut self) -> Result<String> {
+        let quote = match self.rest().chars().next() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => raise!("expected a quoted value at position {}", self.position),
+        };
+        self.position += 1;
+        match self.rest().find(quote) {
+            Some(size) => {
+                let value = unescape(&self.rest()[..size])?;
+                self.position += size + 1;
+                Ok(value)
+            }
+            _ => error!("found an unclosed value at position {}", self.position),
+        }
+    }
+
+    fn skip_prolog(&mut self) -> Result<()> {
+        self.skip_miscellany()?;
+        if self.consume("<?") {
+            self.skip_past("?>")?;
+        }
+        self.skip_miscellany()
+    }
+
+    fn skip_miscellany(&mut self) -> Result<()> {
+        loop {
+            self.skip_whitespace();
+            if self.consume("<!--") {
+                self.skip_past("-->")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn skip_past(&mut self, pattern: &str) -> Result<()> {
+        match self.rest().find(pattern) {
+            Some(offset) => {
+                self.position += offset + pattern.len();
+                Ok(())
+            }
+            _ => error!("expected {pattern:?} after position {}", self.position),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn consume(&mut self, pattern: &str) -> bool {
+        if self.rest().starts_with(pattern) {
+            self.position += pattern.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, pattern: &str) -> Result<()> {
+        if !self.consume(pattern) {
+            raise!("expected {pattern:?} at position {}", self.position);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn rest(&self) -> &str {
+        &self.text[self.position..]
+    }
+}
+
+macro_rules! implement {
+    ($($type:ident),*) => {
+        $(
+            impl Load for $type {
+                fn load(element: &Element) -> Result<Self> {
+                    let value = element.value()?;
+                    match value.parse() {
+                        Ok(value) => Ok(value),
+                        _ => error!(
+                            "found a malformed value {:?} of type {} in element {}",
+                            value,
+                            stringify!($type),
+                            element.name,
+                        ),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+implement!(i8, u8, i16, u16, i32, u32, i64, String);
+
+macro_rules! implement {
+    ($($type:ident . $index:tt),*) => {
+        impl<$($type),*> Load for ($($type,)*)
+        where
+            $($type: Load,)*
+        {
+            fn load(element: &Element) -> Result<Self> {
+                let elements = element.children_named("item").collect::<Vec<_>>();
+                Self::load_all(&elements)
+            }
+
+            fn load_all(elements: &[&Element]) -> Result<Self> {
+                let count = [$($index),*].len();
+                if elements.len() != count {
+                    raise!(
+                        "expected {} elements but found {} for a tuple",
+                        count,
+                        elements.len(),
+                    );
+                }
+                Ok(($($type::load(elements[$index])?,)*))
+            }
+        }
+    };
+}
+
+implement!(A.0);
+implement!(A.0, B.1);
+implement!(A.0, B.1, C.2);
+implement!(A.0, B.1, C.2, D.3);
+implement!(A.0, B.1, C.2, D.3, E.4);
+implement!(A.0, B.1, C.2, D.3, E.4, F.5);
+implement!(A.0, B.1, C.2, D.3, E.4, F.5, G.6);
+implement!(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7);
+
+impl<T: Load, const N: usize> Load for [T; N] {
+    fn load(element: &Element) -> Result<Self> {
+        let elements = element.children_named("item").collect::<Vec<_>>();
+        Self::load_all(&elements)
+    }
+
+    fn load_all(elements: &[&Element]) -> Result<Self> {
+        match Vec::<T>::load_all(elements)?.try_into() {
+            Ok(values) => Ok(values),
+            _ => error!(
+                "expected {} elements but found {} for an array",
+                N,
+                elements.len(),
+            ),
+        }
+    }
+}
+
+impl<T: Load> Load for Vec<T> {
+    fn load(element: &Element) -> Result<Self> {
+        let elements = element.children_named("item").collect::<Vec<_>>();
+        Self::load_all(&elements)
+    }
+
+    fn load_all(elements: &[&Element]) -> Result<Self> {
+        elements.iter().map(|element| T::load(element)).collect()
+    }
+}
+
+impl<T: Load> Load for Box<T> {
+    #[inline]
+    fn load(element: &Element) -> Result<Self> {
+        Ok(Box::new(T::load(element)?))
+    }
+
+    #[inline]
+    fn load_all(elements: &[&Element]) -> Result<Self> {
+        Ok(Box::new(T::load_all(elements)?))
+    }
+}
+
+impl<T: Load> Load for Option<T> {
+    #[inline]
+    fn load(element: &Element) -> Result<Self> {
+        Ok(Some(T::load(element)?))
+    }
+
+    fn load_all(elements: &[&Element]) -> Result<Self> {
+        match elements {
+            [] => Ok(None),
+            _ => Ok(Some(T::load_all(elements)?)),
+        }
+    }
+}
+
+#[doc(hidden)]
+pub fn load_fixed(element: &Element, fraction: u32) -> Result<i64> {
+    let value = element.value()?;
+    match value.parse::<f64>() {
+        Ok(value) if value.is_finite() => {
+            Ok((value * (1u64 << fraction) as f64 + 0.5).floor() as i64)
+        }
+        _ => error!(
+            "found a malformed fixed-point number {:?} in element {}",
+            value, element.name,
+        ),
+    }
+}
+
+#[doc(hidden)]
+pub fn load_flags(element: &Element) -> Result<u64> {
+    let value = element.value()?;
+    let bits = value.replace(' ', "");
+    match u64::from_str_radix(&bits, 2) {
+        Ok(value) => Ok(value),
+        _ => error!(
+            "found malformed flags {:?} in element {}",
+            value, element.name,
+        ),
+    }
+}
+
+#[doc(hidden)]
+pub fn rename(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    for (index, part) in name.split('_').enumerate() {
+        let mut characters = part.chars();
+        match characters.next() {
+            Some(character) if index > 0 => {
+                result.extend(character.to_uppercase());
+                result.push_str(characters.as_str());
+            }
+            _ => result.push_str(part),
+        }
+    }
+    result
+}
+
+fn format_fixed(value: i64, fraction: u32) -> String {
+    if value == 0 {
+        return "0.0".into();
+    }
+    let scale = (1u64 << fraction) as f64;
+    let value = value as f64 / scale;
+    let epsilon = 0.5 / scale;
+    let (lower, upper) = (value - epsilon, value + epsilon);
+    if lower.trunc() != upper.trunc() {
+        return format!("{:.1}", value.round());
+    }
+    let (lower, upper) = (format!("{lower:.8}"), format!("{upper:.8}"));
+    let index = lower
+        .bytes()
+        .zip(upper.bytes())
+        .position(|(one, other)| one != other)
+        .unwrap_or(lower.len());
+    let period = lower.find('.').unwrap_or_default();
+    format!("{:.*}", index.saturating_sub(period), value)
+}
+
+fn format_flags(value: u64, size: usize) -> String {
+    (0..size)
+        .rev()
+        .map(|index| format!("{:08b}", (value >> (8 * index)) as u8))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn escape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for character in value.chars() {
+        match character {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            _ => result.push(character),
+        }
+    }
+    result
+}
+
+fn unescape(value: &str) -> Result<String> {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        let end = match rest[start..].find(';') {
+            Some(end) => start + end,
+            _ => raise!("found a malformed entity in {:?}", value),
+        };
+        let character = match &rest[start + 1..end] {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            entity => match entity.strip_prefix("#x") {
+                Some(code) => u32::from_str_radix(code, 16).ok(),
+                _ => entity.strip_prefix('#').and_then(|code| code.parse().ok()),
+            }
+            .and_then(char::from_u32),
+        };
+        match character {
+            Some(character) => result.push(character),
+            _ => raise!("found a malformed entity in {:?}", value),
+        }
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Element;
+    use crate::{q16, q32};
+
+    flags! {
+        pub Flags(u16) {
+            0b01 => first [FIRST, set_first],
+            0b10 => second [SECOND, set_second],
+            0x100 => third,
+        }
+    }
+
+    flags! {
+        pub SignedFlags(i8) {
+            -0x80 => last [LAST, set_last],
+            0x01 => first [FIRST, set_first],
+        }
+    }
+
+    table! {
+        @ttx
+        @visit
+        @write
+        pub Table {
+            version (q32),
+            angle (q16),
+            flags (Flags),
+            signed_flags (SignedFlags),
+            values ([u16; 2]),
+        }
+    }
+
+    #[test]
+    fn dump() {
+        use crate::tape::{Read, Write};
+
+        let data = vec![
+            0x00, 0x01, 0x80, 0x00, 0x7f, 0xff, 0x01, 0x02, 0x81, 0x00, 0x03, 0xff, 0xfe,
+        ];
+        let table: Table = std::io::Cursor::new(&data).take().unwrap();
+        let element = super::dump(&table, "Table").unwrap();
+        assert_eq!(
+            element.to_string(),
+            r#"<Table>
+  <version value="1.5"/>
+  <angle value="1.99994"/>
+  <flags value="00000001 00000010"/>
+  <signedFlags value="10000001"/>
+  <values index="0" value="3"/>
+  <values index="1" value="65534"/>
+</Table>
+"#,
+        );
+        let table: Table = super::load(&element.to_string()).unwrap();
+        assert!(table.signed_flags.last());
+        let mut other = std::io::Cursor::new(vec![]);
+        other.give(&table).unwrap();
+        assert_eq!(other.into_inner(), data);
+    }
+
+    #[test]
+    fn formats() {
+        table! {
+            @ttx
+            pub Coverage1 {
+                format (u16) = { 1 },
+                glyph_count (u16),
+            }
+        }
+
+        table! {
+            @ttx
+            pub Coverage2 {
+                format (u16) = { 2 },
+                range_count (u16),
+            }
+        }
+
+        formats! {
+            @ttx
+            pub Coverage(u16) {
+                1 => Coverage1(Coverage1),
+                2 => Coverage2(Coverage2),
+            }
+        }
+
+        let text = r#"<Coverage>
+  <rangeCount value="3"/>
+  <format value="2"/>
+</Coverage>"#;
+        let value: Coverage = super::load(text).unwrap();
+        assert!(matches!(
+            value,
+            Coverage::Coverage2(Coverage2 { range_count: 3, .. })
+        ));
+        let text = r#"<Coverage><glyphCount value="4"/><format value="1"/></Coverage>"#;
+        let value: Coverage = super::load(text).unwrap();
+        assert!(matches!(
+            value,
+            Coverage::Coverage1(Coverage1 { glyph_count: 4, .. })
+        ));
+        let text = r#"<Coverage><format value="3"/></Coverage>"#;
+        assert!(super::load::<Coverage>(text).is_err());
+        let text = r#"<Coverage><rangeCount value="3"/></Coverage>"#;
+        assert!(super::load::<Coverage>(text).is_err());
+    }
+
+    #[test]
+    fn fixed() {
+        let cases: Vec<(i64, u32, &str)> = vec![
+            (0x0001_0000, 16, "1.0"),
+            (0x0001_8000, 16, "1.5"),
+            (0x7fff, 14, "1.99994"),
+            (0x0001, 14, "0.00006"),
+            (-0x4000, 14, "-1.0"),
+            (0x1000, 14, "0.25"),
+        ];
+        for (value, fraction, string) in cases.into_iter() {
+            assert_eq!(super::format_fixed(value, fraction), string);
+        }
+    }
+
+    #[test]
+    fn load_fixed() {
+        let cases: Vec<(&str, u32, i64)> = vec![
+            ("1.99994", 14, 0x7fff),
+            ("-2.0", 14, -0x8000),
+            ("0.00006", 14, 0x0001),
+            ("-0.00006", 14, -0x0001),
+            ("0.25", 14, 0x1000),
+            ("1.5", 16, 0x0001_8000),
+            ("-1.5", 16, -0x0001_8000),
+            ("32767.99998", 16, 0x7fff_ffff),
+        ];
+        for (string, fraction, value) in cases.into_iter() {
+            let mut element = Element::new("value");
+            element.attributes.push(("value".into(), string.into()));
+            assert_eq!(super::load_fixed(&element, fraction).unwrap(), value);
+        }
+        for string in ["NaN", "inf", "1.0.0"] {
+            let mut element = Element::new("value");
+            element.attributes.push(("value".into(), string.into()));
+            assert!(super::load_fixed(&element, 14).is_err());
+        }
+        let mut element = Element::new("angle");
+        element.attributes.push(("value".into(), "2.0".into()));
+        assert!(<q16 as super::Load>::load(&element).is_err());
+        element.attributes[0].1 = "-2.00004".into();
+        assert!(<q16 as super::Load>::load(&element).is_err());
+    }
+
+    #[test]
+    fn names() {
+        table! {
+            @ttx
+            @visit
+            pub HorizontalHeader {
+                ascent (i16),
+                descent (i16),
+                line_gap (i16),
+                advance_width_max (u16),
+                min_left_side_bearing (i16),
+                min_right_side_bearing (i16),
+                x_max_extent (i16),
+                caret_slope_rise (i16),
+                caret_slope_run (i16),
+                caret_offset (i16),
+                reserved_0 (i16),
+                reserved_1 (i16),
+                reserved_2 (i16),
+                reserved_3 (i16),
+                metric_data_format (i16),
+                number_of_h_metrics (u16),
+            }
+        }
+
+        let text = r#"<hhea>
+  <ascent value="1854"/>
+  <descent value="-434"/>
+  <lineGap value="67"/>
+  <advanceWidthMax value="2740"/>
+  <minLeftSideBearing value="-1361"/>
+  <minRightSideBearing value="-1360"/>
+  <xMaxExtent value="2566"/>
+  <caretSlopeRise value="1"/>
+  <caretSlopeRun value="0"/>
+  <caretOffset value="0"/>
+  <reserved0 value="0"/>
+  <reserved1 value="0"/>
+  <reserved2 value="0"/>
+  <reserved3 value="0"/>
+  <metricDataFormat value="0"/>
+  <numberOfHMetrics value="3381"/>
+</hhea>
+"#;
+        let table: HorizontalHeader = super::load(text).unwrap();
+        assert_eq!(table.min_left_side_bearing, -1361);
+        assert_eq!(table.number_of_h_metrics, 3381);
+        assert_eq!(super::dump(&table, "hhea").unwrap().to_string(), text);
+    }
+
+    #[test]
+    fn parse() {
+        let element = Element::parse(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <!-- Comment -->
+            <head>
+              <tableVersion value="1.0"/>
+              <name value="A &amp; B &#x41;"/>
+            </head>
+            "#,
+        )
+        .unwrap();
+        assert_eq!(element.name, "head");
+        assert_eq!(element.children.len(), 2);
+        assert_eq!(element.children[1].value().unwrap(), "A & B A");
+        assert_eq!(Element::parse(&element.to_string()).unwrap(), element);
+        assert!(Element::parse("<head><a/></body>").is_err());
+    }
+}
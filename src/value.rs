@@ -2,6 +2,9 @@
 
 use crate::Result;
 
+#[cfg(feature = "derive")]
+pub use typeface_derive::{Read, Write};
+
 /// A type that can be read.
 pub trait Read: Sized {
     /// Read a value.
@@ -14,6 +17,23 @@ pub trait Write {
     fn write<T: crate::tape::Write>(&self, _: &mut T) -> Result<()>;
 }
 
+/// A type whose values all occupy the same number of bytes.
+///
+/// The size is taken from `Size`, and using it fails to compile if the size is not fixed.
+pub trait FixedSize: Read + Size {
+    /// The size in bytes.
+    const SIZE: usize = fixed(<Self as Size>::SIZE);
+}
+
+/// A type whose size when written can be computed without writing.
+pub trait Size {
+    /// The size in bytes if it is the same for all values.
+    const SIZE: Option<usize> = None;
+
+    /// Compute the size in bytes.
+    fn size(&self) -> usize;
+}
+
 macro_rules! read {
     ($tape:ident, $size:expr) => {{
         let mut buffer: [u8; $size] = [0; $size];
@@ -23,42 +43,6 @@ macro_rules! read {
 }
 
 macro_rules! implement {
-    ([i8; 4]) => {
-        impl Read for [i8; 4] {
-            #[inline]
-            fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
-                let value = read!(tape, 4);
-                Ok([
-                    value[0] as i8,
-                    value[1] as i8,
-                    value[2] as i8,
-                    value[3] as i8,
-                ])
-            }
-        }
-
-        impl Write for [i8; 4] {
-            #[inline]
-            fn write<T: crate::tape::Write>(&self, tape: &mut T) -> Result<()> {
-                tape.give(&[self[0] as u8, self[1] as u8, self[2] as u8, self[3] as u8])
-            }
-        }
-    };
-    ([u8; $count:expr]) => {
-        impl Read for [u8; $count] {
-            #[inline]
-            fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
-                Ok(read!(tape, $count))
-            }
-        }
-
-        impl Write for [u8; $count] {
-            #[inline]
-            fn write<T: crate::tape::Write>(&self, tape: &mut T) -> Result<()> {
-                tape.write_all(self)
-            }
-        }
-    };
     ($type:ident, $size:expr) => {
         impl Read for $type {
             #[inline]
@@ -74,6 +58,17 @@ macro_rules! implement {
                 tape.write_all(&value)
             }
         }
+
+        impl Size for $type {
+            const SIZE: Option<usize> = Some($size);
+
+            #[inline]
+            fn size(&self) -> usize {
+                $size
+            }
+        }
+
+        impl FixedSize for $type {}
     };
 }
 
@@ -84,26 +79,93 @@ implement!(u16, 2);
 implement!(i32, 4);
 implement!(i64, 8);
 implement!(u32, 4);
-implement!([i8; 4]);
-implement!([u8; 3]);
-implement!([u8; 4]);
-implement!([u8; 10]);
 
 macro_rules! implement {
-    ($($type:ident),*) => {
-        impl<$($type),*> Read for ($($type),*)
+    ($($type:ident . $index:tt),*) => {
+        impl<$($type),*> Read for ($($type,)*)
         where
             $($type: Read,)*
         {
             #[inline]
             fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
-                Ok(($(tape.take::<$type>()?),*))
+                Ok(($(tape.take::<$type>()?,)*))
             }
         }
+
+        impl<$($type),*> Write for ($($type,)*)
+        where
+            $($type: Write,)*
+        {
+            #[inline]
+            fn write<T: crate::tape::Write>(&self, tape: &mut T) -> Result<()> {
+                $(tape.give(&self.$index)?;)*
+                Ok(())
+            }
+        }
+
+        impl<$($type),*> Size for ($($type,)*)
+        where
+            $($type: Size,)*
+        {
+            const SIZE: Option<usize> = {
+                let size = Some(0);
+                $(let size = add(size, $type::SIZE);)*
+                size
+            };
+
+            #[inline]
+            fn size(&self) -> usize {
+                0 $(+ self.$index.size())*
+            }
+        }
+
+        impl<$($type),*> FixedSize for ($($type,)*) where $($type: FixedSize,)* {}
     };
 }
 
-implement!(U, V);
+implement!(A.0);
+implement!(A.0, B.1);
+implement!(A.0, B.1, C.2);
+implement!(A.0, B.1, C.2, D.3);
+implement!(A.0, B.1, C.2, D.3, E.4);
+implement!(A.0, B.1, C.2, D.3, E.4, F.5);
+implement!(A.0, B.1, C.2, D.3, E.4, F.5, G.6);
+implement!(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7);
+
+impl<U: Read, const N: usize> Read for [U; N] {
+    fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
+        let mut values = Vec::with_capacity(N);
+        for _ in 0..N {
+            values.push(tape.take()?);
+        }
+        match values.try_into() {
+            Ok(values) => Ok(values),
+            Err(values) => error!(
+                "found an array with {} elements instead of {}",
+                values.len(),
+                N,
+            ),
+        }
+    }
+}
+
+impl<U: Write, const N: usize> Write for [U; N] {
+    #[inline]
+    fn write<T: crate::tape::Write>(&self, tape: &mut T) -> Result<()> {
+        tape.give(&self[..])
+    }
+}
+
+impl<U: Size, const N: usize> Size for [U; N] {
+    const SIZE: Option<usize> = multiply(U::SIZE, N);
+
+    #[inline]
+    fn size(&self) -> usize {
+        self[..].size()
+    }
+}
+
+impl<U: FixedSize, const N: usize> FixedSize for [U; N] {}
 
 impl<U: Write> Write for [U] {
     fn write<T: crate::tape::Write>(&self, tape: &mut T) -> Result<()> {
@@ -113,3 +175,91 @@ impl<U: Write> Write for [U] {
         Ok(())
     }
 }
+
+impl<U: Size> Size for [U] {
+    fn size(&self) -> usize {
+        match U::SIZE {
+            Some(size) => size * self.len(),
+            _ => self.iter().map(Size::size).sum(),
+        }
+    }
+}
+
+impl<U: Write> Write for Vec<U> {
+    #[inline]
+    fn write<T: crate::tape::Write>(&self, tape: &mut T) -> Result<()> {
+        tape.give(&self[..])
+    }
+}
+
+impl<U: Size> Size for Vec<U> {
+    #[inline]
+    fn size(&self) -> usize {
+        self[..].size()
+    }
+}
+
+#[doc(hidden)]
+pub const fn fixed(size: Option<usize>) -> usize {
+    match size {
+        Some(size) => size,
+        _ => panic!("found a type whose size is not fixed"),
+    }
+}
+
+#[doc(hidden)]
+pub const fn add(one: Option<usize>, other: Option<usize>) -> Option<usize> {
+    match (one, other) {
+        (Some(one), Some(other)) => Some(one + other),
+        _ => None,
+    }
+}
+
+#[doc(hidden)]
+pub const fn multiply(one: Option<usize>, other: usize) -> Option<usize> {
+    match one {
+        Some(one) => Some(one * other),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::tape::{Read, Write};
+
+    struct Owned(Vec<u8>);
+
+    impl crate::value::Read for Owned {
+        fn read<T: crate::tape::Read>(tape: &mut T) -> crate::Result<Self> {
+            Ok(Owned(tape.take_bytes(1)?))
+        }
+    }
+
+    #[test]
+    fn array() {
+        let value: [u16; 3] = Cursor::new(&[0, 1, 0, 2, 0, 3]).take().unwrap();
+        assert_eq!(value, [1, 2, 3]);
+        let value: [u8; 0] = Cursor::new(&[]).take().unwrap();
+        assert!(value.is_empty());
+        let value: [Owned; 40] = Cursor::new(&[7; 40]).take().unwrap();
+        assert!(value.iter().all(|value| value.0 == [7]));
+        assert!(Cursor::new(&[7; 39]).take::<[Owned; 40]>().is_err());
+
+        let mut tape = Cursor::new(vec![]);
+        tape.give(&[[1u8, 2], [3, 4]]).unwrap();
+        assert_eq!(tape.into_inner(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn tuple() {
+        let value: (u8, i16, [u8; 2]) = Cursor::new(&[1, 0xff, 0xfe, 3, 4]).take().unwrap();
+        assert_eq!(value, (1, -2, [3, 4]));
+        assert!(Cursor::new(&[1, 0xff]).take::<(u8, i16)>().is_err());
+
+        let mut tape = Cursor::new(vec![]);
+        tape.give(&value).unwrap();
+        assert_eq!(tape.into_inner(), [1, 0xff, 0xfe, 3, 4]);
+    }
+}
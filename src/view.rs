@@ -0,0 +1,372 @@
+//! Views over borrowed bytes.
+
+use std::marker::PhantomData;
+
+use crate::value::FixedSize;
+use crate::Result;
+
+/// An array of fixed-size values decoded on access.
+pub struct ArrayView<'l, T> {
+    bytes: &'l [u8],
+    count: usize,
+    marker: PhantomData<T>,
+}
+
+/// An array of fixed-size values sorted by a key and decoded on access.
+pub struct SortedArrayView<'l, T, F> {
+    array: ArrayView<'l, T>,
+    key: F,
+}
+
+/// A field of a mutable view decoded and encoded in place.
+pub struct FieldMut<'l, T> {
+    bytes: &'l mut [u8],
+    offset: usize,
+    value: Option<T>,
+}
+
+/// An iterator over an array of fixed-size values.
+pub struct ArrayViewIter<'l, T> {
+    view: ArrayView<'l, T>,
+    index: usize,
+}
+
+impl<'l, T: FixedSize> ArrayView<'l, T> {
+    /// Create an instance given the bytes and the number of elements.
+    pub fn new(bytes: &'l [u8], count: usize) -> Result<Self> {
+        let size = match count.checked_mul(<T as FixedSize>::SIZE) {
+            Some(size) if size <= bytes.len() => size,
+            _ => raise!(
+                "found an array with {} bytes insufficient for {} elements of size {}",
+                bytes.len(),
+                count,
+                <T as FixedSize>::SIZE,
+            ),
+        };
+        Ok(Self {
+            bytes: &bytes[..size],
+            count,
+            marker: PhantomData,
+        })
+    }
+
+    /// Return the underlying bytes.
+    #[inline]
+    pub fn as_bytes(&self) -> &'l [u8] {
+        self.bytes
+    }
+
+    /// Decode an element if the index is within bounds.
+    pub fn get(&self, index: usize) -> Option<Result<T>> {
+        if index >= self.count {
+            return None;
+        }
+        Some(read(self.bytes, index * <T as FixedSize>::SIZE))
+    }
+
+    /// Return an iterator decoding the elements.
+    #[inline]
+    pub fn iter(&self) -> ArrayViewIter<'l, T> {
+        ArrayViewIter {
+            view: *self,
+            index: 0,
+        }
+    }
+
+    /// Check if the array is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Return the number of elements.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.count
+    }
+}
+
+impl<T> Clone for ArrayView<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ArrayView<'_, T> {}
+
+impl<T> std::fmt::Debug for ArrayView<'_, T> {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter
+            .debug_struct("ArrayView")
+            .field("count", &self.count)
+            .finish()
+    }
+}
+
+impl<'l, T: FixedSize> IntoIterator for &ArrayView<'l, T> {
+    type Item = Result<T>;
+    type IntoIter = ArrayViewIter<'l, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'l, T: FixedSize> IntoIterator for ArrayView<'l, T> {
+    type Item = Result<T>;
+    type IntoIter = ArrayViewIter<'l, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'l, T: FixedSize, F> SortedArrayView<'l, T, F> {
+    /// Create an instance given an array sorted by the key and the key.
+    #[inline]
+    pub fn new<K>(array: ArrayView<'l, T>, key: F) -> Self
+    where
+        F: Fn(&T) -> K,
+        K: Ord,
+    {
+        Self { array, key }
+    }
+
+    /// Search for an element by its key decoding only the probed elements.
+    ///
+    /// The outer result carries decoding errors, and the inner one follows the convention of
+    /// `slice::binary_search`.
+    pub fn binary_search<K>(&self, key: &K) -> Result<std::result::Result<usize, usize>>
+    where
+        F: Fn(&T) -> K,
+        K: Ord,
+    {
+        let (mut lower, mut upper) = (0, self.array.len());
+        while lower < upper {
+            let middle = lower + (upper - lower) / 2;
+            let value = read::<T>(self.array.bytes, middle * <T as FixedSize>::SIZE)?;
+            match (self.key)(&value).cmp(key) {
+                std::cmp::Ordering::Less => lower = middle + 1,
+                std::cmp::Ordering::Greater => upper = middle,
+                std::cmp::Ordering::Equal => return Ok(Ok(middle)),
+            }
+        }
+        Ok(Err(lower))
+    }
+
+    /// Find an element by its key decoding only the probed elements.
+    pub fn find<K>(&self, key: &K) -> Result<Option<T>>
+    where
+        F: Fn(&T) -> K,
+        K: Ord,
+    {
+        match self.binary_search(key)? {
+            Ok(index) => self.array.get(index).transpose(),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl<T, F: Clone> Clone for SortedArrayView<'_, T, F> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            array: self.array,
+            key: self.key.clone(),
+        }
+    }
+}
+
+impl<T, F: Copy> Copy for SortedArrayView<'_, T, F> {}
+
+impl<T, F> std::fmt::Debug for SortedArrayView<'_, T, F> {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter
+            .debug_struct("SortedArrayView")
+            .field("count", &self.array.count)
+            .finish()
+    }
+}
+
+impl<'l, T, F> std::ops::Deref for SortedArrayView<'l, T, F> {
+    type Target = ArrayView<'l, T>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.array
+    }
+}
+
+impl<'l, T: FixedSize + crate::value::Write> FieldMut<'l, T> {
+    #[doc(hidden)]
+    #[inline]
+    pub fn new(bytes: &'l mut [u8], offset: usize, value: Option<T>) -> Self {
+        Self {
+            bytes,
+            offset,
+            value,
+        }
+    }
+
+    /// Decode the value.
+    #[inline]
+    pub fn get(&self) -> Result<T> {
+        read(self.bytes, self.offset)
+    }
+
+    /// Encode a value rejecting those disagreeing with the value of a constant field.
+    pub fn set(&mut self, value: T) -> Result<()>
+    where
+        T: PartialEq + std::fmt::Debug,
+    {
+        if let Some(expected) = &self.value {
+            if expected != &value {
+                raise!(
+                    "found a value {:?} instead of {:?} for a constant field",
+                    value,
+                    expected
+                );
+            }
+        }
+        write(self.bytes, self.offset, &value)
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for FieldMut<'_, T> {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter
+            .debug_struct("FieldMut")
+            .field("offset", &self.offset)
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+#[doc(hidden)]
+pub fn read<T: FixedSize>(bytes: &[u8], offset: usize) -> Result<T> {
+    let mut tape = std::io::Cursor::new(&bytes[offset..(offset + <T as FixedSize>::SIZE)]);
+    crate::value::Read::read(&mut tape)
+}
+
+#[doc(hidden)]
+pub fn write<T>(bytes: &mut [u8], offset: usize, value: &T) -> Result<()>
+where
+    T: FixedSize + crate::value::Write,
+{
+    let mut buffer = Vec::with_capacity(<T as FixedSize>::SIZE);
+    crate::value::Write::write(value, &mut buffer)?;
+    if buffer.len() != <T as FixedSize>::SIZE {
+        raise!(
+            "found a value occupying {} bytes instead of {}",
+            buffer.len(),
+            <T as FixedSize>::SIZE,
+        );
+    }
+    bytes[offset..(offset + <T as FixedSize>::SIZE)].copy_from_slice(&buffer);
+    Ok(())
+}
+
+impl<T> Clone for ArrayViewIter<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            view: self.view,
+            index: self.index,
+        }
+    }
+}
+
+impl<T: FixedSize> Iterator for ArrayViewIter<'_, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.view.get(self.index)?;
+        self.index += 1;
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.view.len() - self.index;
+        (count, Some(count))
+    }
+}
+
+impl<T: FixedSize> ExactSizeIterator for ArrayViewIter<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArrayView, SortedArrayView};
+    use crate::value::FixedSize;
+
+    table! {
+        @fixed
+        pub Record {
+            tag (u32),
+            offset (u16),
+            flags ([u8; 2]),
+        }
+    }
+
+    #[test]
+    fn array() {
+        assert_eq!(<Record as FixedSize>::SIZE, 8);
+        assert_eq!(<(u8, i16, [u32; 3]) as FixedSize>::SIZE, 15);
+
+        let bytes = [
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x10, 0xAA, 0xBB, //
+            0x00, 0x00, 0x00, 0x02, 0x00, 0x20, 0xCC, 0xDD, //
+            0xFF,
+        ];
+        let view = ArrayView::<Record>::new(&bytes, 2).unwrap();
+        assert_eq!(view.len(), 2);
+        assert_eq!(view.as_bytes().len(), 16);
+
+        let record = view.get(1).unwrap().unwrap();
+        assert_eq!(record.tag, 2);
+        assert_eq!(record.offset, 0x20);
+        assert_eq!(record.flags, [0xCC, 0xDD]);
+        assert!(view.get(2).is_none());
+
+        let offsets = view
+            .iter()
+            .map(|record| record.map(|record| record.offset))
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(offsets, [0x10, 0x20]);
+
+        assert!(ArrayView::<Record>::new(&bytes, 3).is_err());
+        assert!(ArrayView::<u16>::new(&bytes, usize::MAX).is_err());
+        assert!(ArrayView::<u16>::new(&[], 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn sorted_array() {
+        let bytes = [
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x10, 0x00, 0x00, //
+            0x00, 0x00, 0x00, 0x03, 0x00, 0x20, 0x00, 0x00, //
+            0x00, 0x00, 0x00, 0x07, 0x00, 0x30, 0x00, 0x00, //
+        ];
+        let array = ArrayView::<Record>::new(&bytes, 3).unwrap();
+        let view = SortedArrayView::new(array, |record: &Record| record.tag);
+        assert_eq!(view.len(), 3);
+        assert_eq!(view.binary_search(&1).unwrap(), Ok(0));
+        assert_eq!(view.binary_search(&7).unwrap(), Ok(2));
+        assert_eq!(view.binary_search(&0).unwrap(), Err(0));
+        assert_eq!(view.binary_search(&5).unwrap(), Err(2));
+        assert_eq!(view.binary_search(&8).unwrap(), Err(3));
+        assert_eq!(view.find(&3).unwrap().unwrap().offset, 0x20);
+        assert!(view.find(&4).unwrap().is_none());
+
+        let offset = 0x10;
+        let view = SortedArrayView::new(array, move |record: &Record| record.offset - offset);
+        assert_eq!(view.find(&0x20).unwrap().unwrap().tag, 7);
+
+        let array = ArrayView::<u16>::new(&[], 0).unwrap();
+        let view = SortedArrayView::new(array, |value: &u16| *value);
+        assert_eq!(view.binary_search(&1).unwrap(), Err(0));
+    }
+}
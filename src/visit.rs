@@ -0,0 +1,156 @@
+//! Types that can be visited.
+
+/// A type that can be visited.
+pub trait Visit {
+    /// Present the value to a visitor.
+    fn visit(&self, _: &mut dyn Visitor);
+}
+
+/// A type that can visit.
+///
+/// Tables and sequences present their parts without visiting them, and it is up to the visitor
+/// to recurse by calling `Visit::visit` on the parts of interest.
+pub trait Visitor {
+    /// Visit a leaf.
+    fn leaf(&mut self, _: Leaf<'_>);
+
+    /// Visit a table given its name and fields.
+    fn table(&mut self, _: &'static str, _: &[Field<'_>]);
+
+    /// Visit a sequence given its elements.
+    fn sequence(&mut self, _: &mut dyn Iterator<Item = &dyn Visit>);
+}
+
+/// A field of a table.
+#[derive(Clone, Copy)]
+pub struct Field<'l> {
+    /// The name.
+    pub name: &'static str,
+    /// The name of the type.
+    pub kind: &'static str,
+    /// The value.
+    pub value: &'l dyn Visit,
+}
+
+/// A value without parts.
+#[derive(Clone, Copy)]
+pub enum Leaf<'l> {
+    /// An absent value.
+    None,
+    /// A signed integer.
+    Signed(i64),
+    /// An unsigned integer.
+    Unsigned(u64),
+    /// A fixed-point number given its raw value and the number of fractional bits.
+    Fixed { value: i64, fraction: u32 },
+    /// A string.
+    String(&'l str),
+    /// A choice given the name of its variant and its raw value.
+    Choice {
+        variant: &'static str,
+        value: &'l dyn Visit,
+    },
+    /// Flags given the names of the set ones, the raw value, and the size in bytes.
+    Flags {
+        names: &'l [&'static str],
+        value: u64,
+        size: usize,
+    },
+}
+
+macro_rules! implement {
+    ($($type:ident => $variant:ident),*) => {
+        $(
+            impl Visit for $type {
+                #[inline]
+                fn visit(&self, visitor: &mut dyn Visitor) {
+                    visitor.leaf(Leaf::$variant((*self).into()));
+                }
+            }
+        )*
+    };
+}
+
+implement!(
+    i8 => Signed,
+    u8 => Unsigned,
+    i16 => Signed,
+    u16 => Unsigned,
+    i32 => Signed,
+    u32 => Unsigned,
+    i64 => Signed
+);
+
+macro_rules! implement {
+    ($($type:ident . $index:tt),*) => {
+        impl<$($type),*> Visit for ($($type,)*)
+        where
+            $($type: Visit,)*
+        {
+            fn visit(&self, visitor: &mut dyn Visitor) {
+                visitor.sequence(&mut [$(&self.$index as &dyn Visit),*].into_iter());
+            }
+        }
+    };
+}
+
+implement!(A.0);
+implement!(A.0, B.1);
+implement!(A.0, B.1, C.2);
+implement!(A.0, B.1, C.2, D.3);
+implement!(A.0, B.1, C.2, D.3, E.4);
+implement!(A.0, B.1, C.2, D.3, E.4, F.5);
+implement!(A.0, B.1, C.2, D.3, E.4, F.5, G.6);
+implement!(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7);
+
+impl<T: Visit, const N: usize> Visit for [T; N] {
+    #[inline]
+    fn visit(&self, visitor: &mut dyn Visitor) {
+        self[..].visit(visitor)
+    }
+}
+
+impl<T: Visit> Visit for [T] {
+    #[inline]
+    fn visit(&self, visitor: &mut dyn Visitor) {
+        visitor.sequence(&mut self.iter().map(|value| value as &dyn Visit))
+    }
+}
+
+impl<T: Visit> Visit for Vec<T> {
+    #[inline]
+    fn visit(&self, visitor: &mut dyn Visitor) {
+        self[..].visit(visitor)
+    }
+}
+
+impl<T: Visit + ?Sized> Visit for Box<T> {
+    #[inline]
+    fn visit(&self, visitor: &mut dyn Visitor) {
+        (**self).visit(visitor)
+    }
+}
+
+impl<T: Visit> Visit for Option<T> {
+    #[inline]
+    fn visit(&self, visitor: &mut dyn Visitor) {
+        match self {
+            Some(value) => value.visit(visitor),
+            _ => visitor.leaf(Leaf::None),
+        }
+    }
+}
+
+impl Visit for str {
+    #[inline]
+    fn visit(&self, visitor: &mut dyn Visitor) {
+        visitor.leaf(Leaf::String(self))
+    }
+}
+
+impl Visit for String {
+    #[inline]
+    fn visit(&self, visitor: &mut dyn Visitor) {
+        visitor.leaf(Leaf::String(self))
+    }
+}
@@ -27,10 +27,96 @@ where
     type Parameter = usize;
 
     fn read<T: crate::tape::Read>(tape: &mut T, count: usize) -> Result<Self> {
-        let mut values = Vec::with_capacity(count);
+        let remaining = usize::try_from(tape.remaining()?).unwrap_or(usize::MAX);
+        let mut values = Vec::with_capacity(count.min(remaining));
         for _ in 0..count {
             values.push(crate::value::Read::read(tape)?);
         }
         Ok(values)
     }
 }
+
+impl<V> Write<'static> for Vec<V>
+where
+    V: crate::value::Write,
+{
+    type Parameter = usize;
+
+    fn write<T: crate::tape::Write>(&self, tape: &mut T, count: usize) -> Result<()> {
+        if self.len() != count {
+            raise!(
+                "found a collection with {} elements unequal to {}",
+                self.len(),
+                count,
+            );
+        }
+        tape.give(&self[..])
+    }
+}
+
+impl<V> Read<'static> for Option<V>
+where
+    V: crate::value::Read,
+{
+    /// Whether the value is present.
+    type Parameter = bool;
+
+    #[inline]
+    fn read<T: crate::tape::Read>(tape: &mut T, present: bool) -> Result<Self> {
+        if present {
+            Ok(Some(tape.take()?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<V> Write<'static> for Option<V>
+where
+    V: crate::value::Write + Default,
+{
+    /// Whether to write the default value in place of an absent one.
+    type Parameter = bool;
+
+    #[inline]
+    fn write<T: crate::tape::Write>(&self, tape: &mut T, null: bool) -> Result<()> {
+        match self {
+            Some(value) => tape.give(value),
+            None if null => tape.give(&V::default()),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::tape::{Read, Write};
+
+    #[test]
+    fn option() {
+        let mut tape = Cursor::new(vec![]);
+        tape.give_given(&Some(1u16), false).unwrap();
+        tape.give_given(&None::<u16>, false).unwrap();
+        tape.give_given(&None::<u16>, true).unwrap();
+        assert_eq!(tape.get_ref(), &[0, 1, 0, 0]);
+        tape.set_position(0);
+        assert_eq!(tape.take_given::<Option<u16>>(true).unwrap(), Some(1));
+        assert_eq!(tape.take_given::<Option<u16>>(false).unwrap(), None);
+        assert_eq!(tape.take_given::<Option<u16>>(true).unwrap(), Some(0));
+    }
+
+    #[test]
+    fn vector() {
+        let value = vec![1u16, 2, 3];
+        let mut tape = Cursor::new(vec![]);
+        tape.give_given(&value, 3).unwrap();
+        assert_eq!(tape.get_ref(), &[0, 1, 0, 2, 0, 3]);
+        assert!(tape.give_given(&value, 2).is_err());
+        tape.set_position(0);
+        assert_eq!(tape.take_given::<Vec<u16>>(3).unwrap(), value);
+        tape.set_position(0);
+        assert!(tape.take_given::<Vec<u16>>(4).is_err());
+    }
+}